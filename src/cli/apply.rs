@@ -0,0 +1,147 @@
+use crate::repo;
+use crate::repository;
+use crate::widget::service_switcher::ServiceSwitcher;
+use crate::Opt;
+
+/// sets the tag of the service and saves the file, returns the exit code
+pub fn run(opt: &Opt, service: &str, tag: &str, verify: bool) -> i32 {
+    if opt.read_only {
+        eprintln!("--read-only doesn't allow changing the file");
+        return 2;
+    }
+    if !is_valid_tag(tag) {
+        eprintln!("{} is not a valid tag", tag);
+        return 2;
+    }
+
+    let mut services = match ServiceSwitcher::new(&opt.file, opt.with_override) {
+        Some(services) => services,
+        None => {
+            eprintln!("No docker-compose file found");
+            return 1;
+        }
+    };
+    let (image, repo) = match select(&mut services, service, tag) {
+        Ok(selected) => selected,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+
+    let mut digest = None;
+    if verify {
+        match repository::find_tag(&repo, tag, &opt.settings()) {
+            Ok(Some(found)) => digest = found.get_digest().map(String::from),
+            Ok(None) => {
+                eprintln!("{} has no tag {}", repo, tag);
+                return 1;
+            }
+            Err(repository::Error::DryRun) => (),
+            Err(e) => {
+                eprintln!("Could not look up the tag: {}", e);
+                return 1;
+            }
+        }
+    }
+
+    services.change_current_line(image);
+    let changes = services.get_changes();
+    if opt.dry_run {
+        for change in changes {
+            println!("would change {}", change);
+        }
+        return 0;
+    }
+    match services.save(opt.backup || opt.config.backup) {
+        Err(e) => {
+            eprintln!("Could not save the file: {}", e);
+            return 1;
+        }
+        Ok(backups) => {
+            for backup in backups {
+                eprintln!("Saved a backup in {}", backup.display());
+            }
+        }
+    }
+    for change in changes {
+        println!("{}", change);
+    }
+    if let Some(digest) = digest {
+        println!("digest: {}", digest);
+    }
+    0
+}
+
+/// selects the image line of the service, returns the new image and the repository of it
+fn select(
+    services: &mut ServiceSwitcher,
+    service: &str,
+    tag: &str,
+) -> Result<(String, String), String> {
+    if !services.select_service(service) {
+        return Err(format!("No image found for the service {}", service));
+    }
+    let current = services.extract_repo().map_err(|e| format!("{}", e))?;
+    // the repository is kept as it is written in the file
+    let (name, _) = repo::split_tag_from_repo(&current).map_err(|e| format!("{}", e))?;
+    let repo = repository::check_repo(name).map_err(|e| format!("{}", e))?;
+    Ok((format!("{}:{}", name, tag), repo))
+}
+
+/// tags as docker allows them
+fn is_valid_tag(tag: &str) -> bool {
+    lazy_static::lazy_static! {
+        static ref REGEX: regex::Regex = regex::Regex::new(r"^[A-Za-z0-9_][A-Za-z0-9_.\-]{0,127}$").unwrap();
+    }
+    REGEX.is_match(tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::widget::service_switcher::ServiceSwitcher;
+
+    #[test]
+    fn test_select() {
+        let path = std::env::temp_dir().join("reel-moby-apply-test.yml");
+        fs::write(
+            &path,
+            "services:\n  web:\n    image: nginx:1.25\n  db:\n    image: postgres:16\n",
+        )
+        .unwrap();
+        let mut services = ServiceSwitcher::new(std::slice::from_ref(&path), false).unwrap();
+
+        assert_eq!(
+            super::select(&mut services, "web", "1.25.3"),
+            Ok(("nginx:1.25.3".to_string(), "library/nginx".to_string()))
+        );
+        assert!(super::select(&mut services, "cache", "7").is_err());
+
+        services.select_service("db");
+        services.change_current_line("postgres:16.2".into());
+        assert!(services.save(false).unwrap().is_empty());
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "services:\n  web:\n    image: nginx:1.25\n  db:\n    image: postgres:16.2\n"
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_is_valid_tag() {
+        let input: Vec<(&str, bool)> = vec![
+            ("1.25.3", true),
+            ("latest", true),
+            ("RC_1-alpine", true),
+            ("", false),
+            (".hidden", false),
+            ("a:b", false),
+        ];
+
+        for i in input {
+            assert_eq!(super::is_valid_tag(i.0), i.1, "{}", i.0);
+        }
+    }
+}
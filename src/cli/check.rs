@@ -0,0 +1,170 @@
+use serde::Serialize;
+
+use crate::common::version;
+use crate::repo;
+use crate::repository;
+use crate::widget::service_switcher::ServiceSwitcher;
+use crate::Opt;
+
+/// pages of tags which are looked through for a newer version of each image
+const CHECK_PAGES: usize = 5;
+
+/// an image of the compose file as printed for scripts
+#[derive(Serialize, Debug, PartialEq)]
+struct CheckOutput {
+    service: Option<String>,
+    current_tag: String,
+    /// null when the tags could not be fetched or the tag is no version like latest
+    latest_tag: Option<String>,
+    outdated: bool,
+}
+
+/// an image line of the compose file
+struct Image {
+    service: Option<String>,
+    repo: String,
+    tag: String,
+    pinned: bool,
+}
+
+/// checks all images of the compose file for newer versions and prints them as json,
+/// returns 1 when an image is outdated and 2 when tags could not be fetched
+pub fn run(opt: &Opt) -> i32 {
+    let mut services = match ServiceSwitcher::new(&opt.file, opt.with_override) {
+        Some(services) => services,
+        None => {
+            super::print_error(&super::ErrorOutput::new(
+                "usage",
+                &"--check needs a docker-compose file",
+            ));
+            return 2;
+        }
+    };
+    services.set_pinned(opt.config.pinned_repos.clone());
+    let images = images(&services);
+
+    let settings = opt.settings();
+    let mut failed = false;
+    let checked = check(&images, |repo| {
+        let names = repository::tag_names(repo, CHECK_PAGES, &settings);
+        match &names {
+            Ok(_) | Err(repository::Error::DryRun) => (),
+            Err(e) => {
+                super::print_error(&super::ErrorOutput::from(e));
+                failed = true;
+            }
+        }
+        names.ok()
+    });
+    // only the requests are printed, like with --json
+    if opt.dry_run {
+        return 0;
+    }
+
+    if let Err(e) = serde_json::to_string_pretty(&checked).map(|json| println!("{}", json)) {
+        super::print_error(&super::ErrorOutput::new("converting", &e));
+        return 2;
+    }
+    match (checked.iter().any(|c| c.outdated), failed) {
+        (true, _) => 1,
+        (false, true) => 2,
+        (false, false) => 0,
+    }
+}
+
+/// the images of the file with the repository as the registry knows it
+fn images(services: &ServiceSwitcher) -> Vec<Image> {
+    services
+        .get_image_lines()
+        .into_iter()
+        .filter_map(|(i, image)| {
+            let (name, tag) = repo::split_tag_from_repo(&image).ok()?;
+            Some(Image {
+                service: services.service_name(i).map(String::from),
+                repo: repository::check_repo(name).ok()?,
+                // docker uses latest for images without a tag
+                tag: match tag {
+                    "" => String::from("latest"),
+                    tag => tag.to_string(),
+                },
+                pinned: services.is_pinned(i),
+            })
+        })
+        .collect()
+}
+
+/// compares the tag of each image with the newest version of the same form,
+/// pinned images are never outdated
+fn check<F>(images: &[Image], mut fetch: F) -> Vec<CheckOutput>
+where
+    F: FnMut(&str) -> Option<Vec<String>>,
+{
+    images
+        .iter()
+        .map(|image| {
+            let latest = fetch(&image.repo).and_then(|names| {
+                let names: Vec<&str> = names.iter().map(String::as_str).collect();
+                version::newest_version(&image.tag, &names).map(String::from)
+            });
+            let outdated = !image.pinned
+                && latest
+                    .as_deref()
+                    .is_some_and(|l| version::is_newer(l, &image.tag));
+            CheckOutput {
+                service: image.service.clone(),
+                current_tag: image.tag.clone(),
+                latest_tag: latest,
+                outdated,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CheckOutput, Image};
+
+    #[test]
+    fn test_check() {
+        let image = |service: &str, repo: &str, tag: &str, pinned: bool| Image {
+            service: Some(service.to_string()),
+            repo: repo.to_string(),
+            tag: tag.to_string(),
+            pinned,
+        };
+        let images = vec![
+            image("web", "library/nginx", "1.25.3", false),
+            image("proxy", "library/nginx", "1.27.0", false),
+            image("db", "library/postgres", "16.1", true),
+            image("cache", "library/redis", "latest", false),
+            image("queue", "library/rabbitmq", "3.12.0", false),
+        ];
+        let fetch = |repo: &str| -> Option<Vec<String>> {
+            let names: &[&str] = match repo {
+                "library/nginx" => &["1.27.0", "1.25.3", "1.27.0-alpine", "latest"],
+                "library/postgres" => &["16.2", "16.1"],
+                "library/redis" => &["7.2.4", "latest"],
+                _ => return None,
+            };
+            Some(names.iter().map(|n| n.to_string()).collect())
+        };
+        let output =
+            |service: &str, current: &str, latest: Option<&str>, outdated: bool| CheckOutput {
+                service: Some(service.to_string()),
+                current_tag: current.to_string(),
+                latest_tag: latest.map(String::from),
+                outdated,
+            };
+
+        assert_eq!(
+            super::check(&images, fetch),
+            vec![
+                output("web", "1.25.3", Some("1.27.0"), true),
+                output("proxy", "1.27.0", Some("1.27.0"), false),
+                output("db", "16.1", Some("16.2"), false),
+                output("cache", "latest", None, false),
+                output("queue", "3.12.0", None, false),
+            ]
+        );
+    }
+}
@@ -0,0 +1,141 @@
+pub mod apply;
+pub mod check;
+
+use serde::Serialize;
+
+use crate::repository;
+use crate::Opt;
+
+/// a tag as printed for scripts
+#[derive(Serialize)]
+struct TagOutput {
+    name: String,
+    last_updated: Option<String>,
+    /// null when the registry doesn't list digests, so the fields stay the same
+    digest: Option<String>,
+}
+
+impl From<&repository::Tag> for TagOutput {
+    fn from(tag: &repository::Tag) -> Self {
+        Self {
+            name: tag.get_name().to_string(),
+            last_updated: tag.last_updated().map(|d| d.to_rfc3339()),
+            digest: tag.get_digest().map(String::from),
+        }
+    }
+}
+
+/// an error as printed for scripts
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ErrorOutput {
+    /// stable identifier scripts can match on
+    error: &'static str,
+    detail: String,
+}
+
+impl ErrorOutput {
+    pub fn new(error: &'static str, detail: &dyn std::fmt::Display) -> Self {
+        Self {
+            error,
+            detail: format!("{}", detail),
+        }
+    }
+}
+
+impl From<&repository::Error> for ErrorOutput {
+    fn from(e: &repository::Error) -> Self {
+        Self::new(e.code(), e)
+    }
+}
+
+/// print the error as json to stderr
+fn print_error(error: &ErrorOutput) {
+    match serde_json::to_string(error) {
+        Ok(json) => eprintln!("{}", json),
+        Err(e) => eprintln!("{}: {}", error.detail, e),
+    }
+}
+
+/// runs without a ui and prints json, returns the exit code
+pub fn run(opt: &Opt) -> i32 {
+    let name = match &opt.repo {
+        Some(name) => name,
+        None => {
+            print_error(&ErrorOutput::new("usage", &"--json needs a --repo"));
+            return 2;
+        }
+    };
+
+    let repo = match repository::Repo::new(name, &opt.settings()) {
+        Ok(repo) => repo,
+        Err(repository::Error::DryRun) => return 0,
+        Err(e) => {
+            print_error(&ErrorOutput::from(&e));
+            return 1;
+        }
+    };
+
+    if let Some(warning) = repo.get_warning() {
+        eprintln!("{}", warning);
+    }
+    let tags: Vec<TagOutput> = repo.get_tags().iter().map(TagOutput::from).collect();
+
+    match serde_json::to_string_pretty(&tags) {
+        Ok(json) => {
+            println!("{}", json);
+            0
+        }
+        Err(e) => {
+            print_error(&ErrorOutput::new("converting", &e));
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::repository::Error;
+
+    #[test]
+    fn test_error_output() {
+        let input: Vec<(Error, &str)> = vec![
+            (
+                Error::Fetching("dns".into()),
+                r#"{"error":"fetching","detail":"Fetching error: dns"}"#,
+            ),
+            (
+                Error::Converting("bad".into()),
+                r#"{"error":"converting","detail":"Converting error: bad"}"#,
+            ),
+            (
+                Error::NoTagsFound,
+                r#"{"error":"no_tags_found","detail":"Given Repo has 0 tags. Is it valid?"}"#,
+            ),
+        ];
+
+        for i in input {
+            let output = super::ErrorOutput::from(&i.0);
+            assert_eq!(serde_json::to_string(&output).unwrap(), i.1);
+        }
+    }
+
+    #[test]
+    fn test_tag_output() {
+        let input: Vec<(&str, &str)> = vec![
+            (
+                r#"{"name": "1.25", "details": [], "last_updated": "2024-03-01T12:00:00Z", "digest": "sha256:a"}"#,
+                r#"{"name":"1.25","last_updated":"2024-03-01T12:00:00+00:00","digest":"sha256:a"}"#,
+            ),
+            (
+                r#"{"name": "1.25", "details": [], "last_updated": null}"#,
+                r#"{"name":"1.25","last_updated":null,"digest":null}"#,
+            ),
+        ];
+
+        for i in input {
+            let tag: crate::repository::Tag = serde_json::from_str(i.0).unwrap();
+            let output = super::TagOutput::from(&tag);
+            assert_eq!(serde_json::to_string(&output).unwrap(), i.1);
+        }
+    }
+}
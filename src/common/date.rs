@@ -0,0 +1,84 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use regex::Regex;
+
+/// parses an absolute date like 2024-01-01 or a relative one like 30d
+pub fn parse_since(input: &str) -> Result<DateTime<Utc>, String> {
+    parse_since_from(input, Utc::now())
+}
+
+fn parse_since_from(input: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    lazy_static::lazy_static! {
+        static ref REGEX: Regex = Regex::new(r"^(\d+)([hdwmy])$").unwrap();
+    }
+    let input = input.trim();
+
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        return Ok(DateTime::from_utc(date.and_hms(0, 0, 0), Utc));
+    }
+    if let Ok(date) = DateTime::parse_from_rfc3339(input) {
+        return Ok(date.with_timezone(&Utc));
+    }
+
+    let caps = match REGEX.captures(input) {
+        Some(caps) => caps,
+        None => {
+            return Err(format!(
+                "invalid date '{}', expected e.g. 2024-01-01 or 30d",
+                input
+            ))
+        }
+    };
+    let amount: i64 = match caps[1].parse() {
+        Ok(amount) => amount,
+        Err(e) => return Err(format!("invalid number in '{}': {}", input, e)),
+    };
+    let hours_per_unit = match &caps[2] {
+        "h" => 1,
+        "d" => 24,
+        "w" => 24 * 7,
+        "m" => 24 * 30,
+        _ => 24 * 365,
+    };
+    // durations are limited to i64::MAX milliseconds, dates to about 262000 years
+    let too_far = || format!("'{}' is too far in the past", input);
+    let hours = match amount.checked_mul(hours_per_unit) {
+        Some(hours) if hours <= i64::MAX / 1000 / 3600 => hours,
+        _ => return Err(too_far()),
+    };
+    now.checked_sub_signed(Duration::hours(hours))
+        .ok_or_else(too_far)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+
+    #[test]
+    fn test_parse_since() {
+        let now = Utc.ymd(2024, 3, 1).and_hms(12, 0, 0);
+        let input: Vec<(&str, Option<DateTime<Utc>>)> = vec![
+            ("2024-01-01", Some(Utc.ymd(2024, 1, 1).and_hms(0, 0, 0))),
+            (
+                "2024-01-01T10:00:00+02:00",
+                Some(Utc.ymd(2024, 1, 1).and_hms(8, 0, 0)),
+            ),
+            ("12h", Some(now - Duration::hours(12))),
+            ("30d", Some(now - Duration::days(30))),
+            (" 2w ", Some(now - Duration::weeks(2))),
+            ("6m", Some(now - Duration::days(180))),
+            ("1y", Some(now - Duration::days(365))),
+            ("", None),
+            ("yesterday", None),
+            ("30x", None),
+            ("2024-13-01", None),
+            ("1000000y", None),
+            ("99999999999y", None),
+            ("9223372036854775807h", None),
+            ("99999999999999999999d", None),
+        ];
+
+        for i in input {
+            assert_eq!(super::parse_since_from(i.0, now).ok(), i.1, "{}", i.0);
+        }
+    }
+}
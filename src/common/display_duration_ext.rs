@@ -1,28 +1,215 @@
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+/// the language of displayed durations
+#[derive(Deserialize, Default, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    English,
+    German,
+}
+
+/// the locale chosen in the config, set once at the start
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+pub fn set_locale(locale: Locale) {
+    let _ = LOCALE.set(locale);
+}
+
+fn locale() -> Locale {
+    LOCALE.get().copied().unwrap_or_default()
+}
+
 pub trait DisplayDurationExt {
-    /// displays a duration in a human readable form
-    fn display(&self) -> String;
+    /// displays a duration in a human readable form, e.g. 3 Days
+    fn display(&self) -> String {
+        self.display_in(locale())
+    }
+
+    /// e.g. 3 Days ago
+    fn ago(&self) -> String {
+        self.ago_in(locale())
+    }
+
+    /// e.g. 3 Days old
+    fn old(&self) -> String {
+        self.old_in(locale())
+    }
+
+    fn display_in(&self, locale: Locale) -> String;
+
+    fn ago_in(&self, locale: Locale) -> String;
+
+    fn old_in(&self, locale: Locale) -> String;
+}
+
+#[derive(Clone, Copy)]
+enum Unit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Year,
+}
+
+/// the largest unit of which the duration has at least one, e.g. 1 Year for 80 weeks
+fn largest_unit(duration: &chrono::Duration) -> (i64, Unit) {
+    if *duration < chrono::Duration::zero() {
+        // e.g. a clock which is behind the one of the registry
+        (0, Unit::Second)
+    } else if duration.num_weeks() >= 52 {
+        (duration.num_weeks() / 52, Unit::Year)
+    } else if duration.num_days() >= 1 {
+        (duration.num_days(), Unit::Day)
+    } else if duration.num_hours() >= 1 {
+        (duration.num_hours(), Unit::Hour)
+    } else if duration.num_minutes() >= 1 {
+        (duration.num_minutes(), Unit::Minute)
+    } else {
+        (duration.num_seconds(), Unit::Second)
+    }
+}
+
+/// the word for the unit, german words after "vor" take the dative case
+fn word(locale: Locale, unit: Unit, count: i64, dative: bool) -> &'static str {
+    let one = count == 1;
+    match (locale, unit) {
+        (Locale::English, Unit::Second) if one => "Second",
+        (Locale::English, Unit::Second) => "Seconds",
+        (Locale::English, Unit::Minute) if one => "Minute",
+        (Locale::English, Unit::Minute) => "Minutes",
+        (Locale::English, Unit::Hour) if one => "Hour",
+        (Locale::English, Unit::Hour) => "Hours",
+        (Locale::English, Unit::Day) if one => "Day",
+        (Locale::English, Unit::Day) => "Days",
+        (Locale::English, Unit::Year) if one => "Year",
+        (Locale::English, Unit::Year) => "Years",
+        (Locale::German, Unit::Second) if one => "Sekunde",
+        (Locale::German, Unit::Second) => "Sekunden",
+        (Locale::German, Unit::Minute) if one => "Minute",
+        (Locale::German, Unit::Minute) => "Minuten",
+        (Locale::German, Unit::Hour) if one => "Stunde",
+        (Locale::German, Unit::Hour) => "Stunden",
+        (Locale::German, Unit::Day) if one => "Tag",
+        (Locale::German, Unit::Day) if dative => "Tagen",
+        (Locale::German, Unit::Day) => "Tage",
+        (Locale::German, Unit::Year) if one => "Jahr",
+        (Locale::German, Unit::Year) if dative => "Jahren",
+        (Locale::German, Unit::Year) => "Jahre",
+    }
 }
 
 impl DisplayDurationExt for chrono::Duration {
-    fn display(&self) -> String {
-        if self.num_weeks() == 52 {
-            format!("{} Year", (self.num_weeks() / 52) as i32)
-        } else if self.num_weeks() > 103 {
-            format!("{} Years", (self.num_weeks() / 52) as i32)
-        } else if self.num_days() == 1 {
-            format!("{} Day", self.num_days())
-        } else if self.num_days() > 1 {
-            format!("{} Days", self.num_days())
-        } else if self.num_hours() == 1 {
-            format!("{} Hour", self.num_hours())
-        } else if self.num_hours() > 1 {
-            format!("{} Hours", self.num_hours())
-        } else if self.num_minutes() == 1 {
-            format!("{} Minute", self.num_minutes())
-        } else if self.num_minutes() > 1 {
-            format!("{} Minutes", self.num_minutes())
-        } else {
-            format!("{} Seconds", self.num_seconds())
+    fn display_in(&self, locale: Locale) -> String {
+        let (count, unit) = largest_unit(self);
+        format!("{} {}", count, word(locale, unit, count, false))
+    }
+
+    fn ago_in(&self, locale: Locale) -> String {
+        let (count, unit) = largest_unit(self);
+        match locale {
+            Locale::English => format!("{} {} ago", count, word(locale, unit, count, false)),
+            Locale::German => format!("vor {} {}", count, word(locale, unit, count, true)),
+        }
+    }
+
+    fn old_in(&self, locale: Locale) -> String {
+        match locale {
+            Locale::English => format!("{} old", self.display_in(locale)),
+            Locale::German => format!("{} alt", self.display_in(locale)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::{DisplayDurationExt, Locale};
+
+    #[test]
+    fn test_display() {
+        let input: Vec<(Duration, &str)> = vec![
+            (Duration::zero(), "0 Seconds"),
+            (Duration::seconds(-30), "0 Seconds"),
+            (Duration::seconds(1), "1 Second"),
+            (Duration::minutes(1), "1 Minute"),
+            (Duration::hours(1), "1 Hour"),
+            (Duration::hours(5), "5 Hours"),
+            (Duration::days(1), "1 Day"),
+            (Duration::days(6), "6 Days"),
+            (Duration::weeks(1), "7 Days"),
+            (Duration::weeks(52) - Duration::days(1), "363 Days"),
+            (Duration::weeks(52), "1 Year"),
+            (Duration::weeks(80), "1 Year"),
+            (Duration::weeks(104), "2 Years"),
+            (Duration::weeks(160), "3 Years"),
+        ];
+
+        for i in input {
+            assert_eq!(i.0.display_in(Locale::English), i.1);
+        }
+    }
+
+    #[test]
+    fn test_locales() {
+        let input: Vec<(Duration, Locale, &str, &str)> = vec![
+            (
+                Duration::seconds(1),
+                Locale::English,
+                "1 Second ago",
+                "1 Second old",
+            ),
+            (
+                Duration::days(3),
+                Locale::English,
+                "3 Days ago",
+                "3 Days old",
+            ),
+            (
+                Duration::seconds(1),
+                Locale::German,
+                "vor 1 Sekunde",
+                "1 Sekunde alt",
+            ),
+            (
+                Duration::minutes(1),
+                Locale::German,
+                "vor 1 Minute",
+                "1 Minute alt",
+            ),
+            (
+                Duration::hours(1),
+                Locale::German,
+                "vor 1 Stunde",
+                "1 Stunde alt",
+            ),
+            (Duration::days(1), Locale::German, "vor 1 Tag", "1 Tag alt"),
+            (
+                Duration::weeks(1),
+                Locale::German,
+                "vor 7 Tagen",
+                "7 Tage alt",
+            ),
+            (
+                Duration::weeks(60),
+                Locale::German,
+                "vor 1 Jahr",
+                "1 Jahr alt",
+            ),
+            (
+                Duration::weeks(110),
+                Locale::German,
+                "vor 2 Jahren",
+                "2 Jahre alt",
+            ),
+        ];
+
+        for i in input {
+            assert_eq!(i.0.ago_in(i.1), i.2);
+            assert_eq!(i.0.old_in(i.1), i.3);
         }
     }
 }
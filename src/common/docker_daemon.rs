@@ -0,0 +1,110 @@
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+const DEFAULT_SOCKET: &str = "/var/run/docker.sock";
+
+/// a running container as reported by the docker daemon
+#[derive(Debug, PartialEq, Clone)]
+pub struct Container {
+    /// the compose service the container belongs to
+    pub service: Option<String>,
+    /// the image reference the container was started with, e.g. nginx:1.25
+    pub image: String,
+}
+
+#[derive(Deserialize)]
+struct ContainerJson {
+    #[serde(rename(deserialize = "Image"))]
+    image: String,
+    #[serde(rename(deserialize = "Labels"), default)]
+    labels: std::collections::HashMap<String, String>,
+}
+
+/// asks the docker daemon for all running containers
+pub fn running_containers() -> Result<Vec<Container>, String> {
+    let socket = match std::env::var("DOCKER_HOST") {
+        Ok(host) => match host.strip_prefix("unix://") {
+            Some(path) => path.to_string(),
+            None => return Err(format!("unsupported DOCKER_HOST {}", host)),
+        },
+        Err(_) => String::from(DEFAULT_SOCKET),
+    };
+
+    let mut stream = match UnixStream::connect(&socket) {
+        Ok(stream) => stream,
+        Err(e) => return Err(format!("could not connect to {}: {}", socket, e)),
+    };
+    let timeout = Some(Duration::from_secs(2));
+    if let Err(e) = stream
+        .set_read_timeout(timeout)
+        .and(stream.set_write_timeout(timeout))
+    {
+        return Err(format!("{}", e));
+    }
+
+    // http 1.0 avoids chunked answers
+    let request = "GET /containers/json HTTP/1.0\r\nHost: docker\r\n\r\n";
+    let mut response = String::new();
+    if let Err(e) = stream
+        .write_all(request.as_bytes())
+        .and(stream.read_to_string(&mut response))
+    {
+        return Err(format!("docker daemon did not answer: {}", e));
+    }
+
+    parse_response(&response)
+}
+
+fn parse_response(response: &str) -> Result<Vec<Container>, String> {
+    let (head, body) = match response.split_once("\r\n\r\n") {
+        Some(split) => split,
+        None => return Err(String::from("invalid answer of docker daemon")),
+    };
+    if !head.starts_with("HTTP/1.0 200") && !head.starts_with("HTTP/1.1 200") {
+        let status = head.lines().next().unwrap_or_default();
+        return Err(format!("docker daemon answered {}", status));
+    }
+
+    let containers: Vec<ContainerJson> = match serde_json::from_str(body) {
+        Ok(containers) => containers,
+        Err(e) => return Err(format!("invalid json: {}", e)),
+    };
+    Ok(containers
+        .into_iter()
+        .map(|c| Container {
+            service: c.labels.get("com.docker.compose.service").cloned(),
+            image: c.image,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Container;
+
+    #[test]
+    fn test_parse_response() {
+        let response = "HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n[
+            {\"Image\":\"nginx:1.25\",\"Labels\":{\"com.docker.compose.service\":\"web\"}},
+            {\"Image\":\"sha256:abc\",\"Labels\":{}}
+        ]";
+        assert_eq!(
+            super::parse_response(response),
+            Ok(vec![
+                Container {
+                    service: Some("web".into()),
+                    image: "nginx:1.25".into()
+                },
+                Container {
+                    service: None,
+                    image: "sha256:abc".into()
+                },
+            ])
+        );
+
+        assert!(super::parse_response("HTTP/1.0 500 Error\r\n\r\n").is_err());
+    }
+}
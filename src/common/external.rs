@@ -0,0 +1,303 @@
+use std::fmt;
+use std::io::{Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// time an external program gets before it is killed
+const TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// none of the known programs could be started
+    NotAvailable(&'static str),
+    /// the program exited with an error
+    Failed(String),
+    /// the program did not finish in time and was killed
+    TimedOut(&'static str),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotAvailable(s) => write!(f, "no program found for {}", s),
+            Error::Failed(s) => write!(f, "external program failed: {}", s),
+            Error::TimedOut(s) => write!(f, "timed out {}", s),
+        }
+    }
+}
+
+/// actions which need a program outside of reel-moby
+pub enum Action {
+    CopyToClipboard(String),
+    OpenBrowser(String),
+}
+
+impl Action {
+    fn description(&self) -> &'static str {
+        match self {
+            Action::CopyToClipboard(_) => "copying to clipboard",
+            Action::OpenBrowser(_) => "opening browser",
+        }
+    }
+
+    fn success(&self) -> String {
+        match self {
+            Action::CopyToClipboard(text) => match text.lines().count() {
+                0 | 1 => String::from("Copied to clipboard"),
+                count => format!("Copied {} lines to clipboard", count),
+            },
+            Action::OpenBrowser(_) => String::from("Opened browser"),
+        }
+    }
+
+    /// programs which may handle the action, in the order they are tried
+    fn candidates(&self) -> Vec<Command> {
+        match self {
+            Action::CopyToClipboard(_) => {
+                let mut list = vec![];
+                if cfg!(target_os = "macos") {
+                    list.push(Command::new("pbcopy"));
+                }
+                if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+                    list.push(Command::new("wl-copy"));
+                }
+                let mut xclip = Command::new("xclip");
+                xclip.args(["-selection", "clipboard"]);
+                list.push(xclip);
+                let mut xsel = Command::new("xsel");
+                xsel.args(["--clipboard", "--input"]);
+                list.push(xsel);
+                list
+            }
+            Action::OpenBrowser(url) => {
+                let mut list = vec![];
+                if let Ok(browser) = std::env::var("BROWSER") {
+                    let mut cmd = Command::new(browser);
+                    cmd.arg(url);
+                    list.push(cmd);
+                }
+                let mut cmd = match cfg!(target_os = "macos") {
+                    true => Command::new("open"),
+                    false => Command::new("xdg-open"),
+                };
+                cmd.arg(url);
+                list.push(cmd);
+                list
+            }
+        }
+    }
+
+    /// text which is given to the program through stdin
+    fn input(&self) -> Option<&str> {
+        match self {
+            Action::CopyToClipboard(text) => Some(text),
+            Action::OpenBrowser(_) => None,
+        }
+    }
+
+    /// run the action on a separate thread to never block the ui
+    pub fn spawn(self) -> Pending {
+        let (tx, rx) = mpsc::channel();
+        let success = self.success();
+        thread::spawn(move || {
+            // the receiver may already be gone, which is fine
+            let _ = tx.send(self.run(TIMEOUT));
+        });
+        Pending {
+            receiver: rx,
+            success,
+        }
+    }
+
+    /// try all candidates until one could be started
+    fn run(&self, timeout: Duration) -> Result<(), Error> {
+        for mut cmd in self.candidates() {
+            match run_with_timeout(&mut cmd, self.input(), timeout) {
+                Err(Error::NotAvailable(_)) => continue,
+                Err(Error::TimedOut(_)) => return Err(Error::TimedOut(self.description())),
+                result => return result,
+            }
+        }
+        Err(Error::NotAvailable(self.description()))
+    }
+}
+
+/// an action which is running in the background
+pub struct Pending {
+    receiver: mpsc::Receiver<Result<(), Error>>,
+    success: String,
+}
+
+impl Pending {
+    /// returns the message to display, when the action has finished
+    pub fn poll(&self) -> Option<String> {
+        match self.receiver.try_recv() {
+            Ok(Ok(())) => Some(self.success.clone()),
+            Ok(Err(e)) => Some(format!("{}", e)),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => Some(String::from("external action aborted")),
+        }
+    }
+}
+
+/// open the file in $VISUAL or $EDITOR and wait until the editor is closed,
+/// it uses the terminal, so the ui has to leave raw mode before
+pub fn edit(path: &std::path::Path) -> Result<(), Error> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok();
+    let (program, args) = editor_command(editor.as_deref());
+    match Command::new(program).args(args).arg(path).status() {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(Error::Failed(format!("editor {}", status))),
+        Err(_) => Err(Error::NotAvailable("editing the file")),
+    }
+}
+
+/// the program and its arguments, editors like `code -w` come with arguments
+fn editor_command(editor: Option<&str>) -> (&str, Vec<&str>) {
+    let mut parts = editor.unwrap_or_default().split_whitespace();
+    match parts.next() {
+        Some(program) => (program, parts.collect()),
+        None => ("vi", vec![]),
+    }
+}
+
+/// start the command and kill it, when it does not finish in time
+fn run_with_timeout(
+    cmd: &mut Command,
+    input: Option<&str>,
+    timeout: Duration,
+) -> Result<(), Error> {
+    // programs like xclip keep running in the background, so stdout is not captured
+    let child = match cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(_) => return Err(Error::NotAvailable("command")),
+    };
+
+    wait_with_timeout(child, input, timeout)
+}
+
+/// start the command and return its output, when it finishes in time
+pub fn output_with_timeout(
+    cmd: &mut Command,
+    input: Option<&str>,
+    timeout: Duration,
+) -> Result<String, Error> {
+    let mut child = match cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(_) => return Err(Error::NotAvailable("command")),
+    };
+
+    // read in parallel, so a full pipe does not block the program
+    let mut stdout = child.stdout.take().unwrap();
+    let reader = thread::spawn(move || {
+        let mut output = String::new();
+        let _ = stdout.read_to_string(&mut output);
+        output
+    });
+
+    wait_with_timeout(child, input, timeout)?;
+    match reader.join() {
+        Ok(output) => Ok(output),
+        Err(_) => Err(Error::Failed(String::from("could not read output"))),
+    }
+}
+
+/// pass the input to the child and wait until it exits or the timeout is reached
+fn wait_with_timeout(
+    mut child: Child,
+    input: Option<&str>,
+    timeout: Duration,
+) -> Result<(), Error> {
+    if let Some(text) = input {
+        if let Some(mut stdin) = child.stdin.take() {
+            if let Err(e) = stdin.write_all(text.as_bytes()) {
+                let _ = child.kill();
+                return Err(Error::Failed(format!("{}", e)));
+            }
+        }
+    }
+    // close stdin, so the program knows the input is complete
+    drop(child.stdin.take());
+
+    let start = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(status)) => return Err(Error::Failed(format!("{}", status))),
+            Ok(None) if start.elapsed() > timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(Error::TimedOut("command"));
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(20)),
+            Err(e) => return Err(Error::Failed(format!("{}", e))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use std::time::{Duration, Instant};
+
+    use super::Error;
+
+    #[test]
+    fn test_run_with_timeout() {
+        let mut cmd = Command::new("sleep");
+        cmd.arg("5");
+        let start = Instant::now();
+        let result = super::run_with_timeout(&mut cmd, None, Duration::from_millis(100));
+        assert_eq!(result, Err(Error::TimedOut("command")));
+        assert!(start.elapsed() < Duration::from_secs(2));
+
+        let mut cmd = Command::new("cat");
+        assert_eq!(
+            super::run_with_timeout(&mut cmd, Some("text"), Duration::from_secs(2)),
+            Ok(())
+        );
+
+        let mut cmd = Command::new("cat");
+        assert_eq!(
+            super::output_with_timeout(&mut cmd, Some("text"), Duration::from_secs(2)),
+            Ok(String::from("text"))
+        );
+
+        let mut cmd = Command::new("this-program-does-not-exist");
+        assert_eq!(
+            super::run_with_timeout(&mut cmd, None, Duration::from_secs(2)),
+            Err(Error::NotAvailable("command"))
+        );
+    }
+
+    #[test]
+    fn test_editor_command() {
+        let input: Vec<(Option<&str>, &str)> = vec![
+            (None, "vi"),
+            (Some(""), "vi"),
+            (Some("nano"), "nano"),
+            (Some("code -w"), "code -w"),
+            (Some(" emacs  -nw "), "emacs -nw"),
+        ];
+
+        for i in input {
+            let (program, args) = super::editor_command(i.0);
+            let command = [vec![program], args].concat().join(" ");
+            assert_eq!(command, i.1, "{:?}", i.0);
+        }
+    }
+}
@@ -1 +1,6 @@
+pub mod date;
 pub mod display_duration_ext;
+pub mod docker_daemon;
+pub mod external;
+pub mod tag_policy;
+pub mod version;
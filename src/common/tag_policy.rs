@@ -0,0 +1,104 @@
+use regex::Regex;
+
+/// tags which usually move to newer images
+const DEFAULT_MUTABLE: [&str; 4] = [
+    r"^(latest|stable|edge|nightly|main|master|develop)(-.+)?$",
+    r"^.+-(latest|stable)$",
+    // only a major or major.minor version like 1 or 1.25-alpine
+    r"^v?\d{1,3}(-.+)?$",
+    r"^v?\d{1,3}\.\d+(-.+)?$",
+];
+
+/// decides which tags are mutable and should be pinned instead
+#[derive(Clone, Debug)]
+pub struct TagPolicy {
+    mutable: Vec<Regex>,
+    /// tags matching these are never warned about
+    pinned: Vec<Regex>,
+}
+
+impl Default for TagPolicy {
+    fn default() -> Self {
+        Self {
+            mutable: compile(&DEFAULT_MUTABLE).unwrap(),
+            pinned: vec![],
+        }
+    }
+}
+
+impl TagPolicy {
+    /// the patterns of mutable tags replace the default ones when they are given
+    pub fn new(mutable: Option<&[String]>, pinned: &[String]) -> Result<Self, String> {
+        let mutable = match mutable {
+            None => compile(&DEFAULT_MUTABLE)?,
+            Some(patterns) => compile(patterns)?,
+        };
+        Ok(Self {
+            mutable,
+            pinned: compile(pinned)?,
+        })
+    }
+
+    pub fn is_mutable(&self, tag: &str) -> bool {
+        self.mutable.iter().any(|r| r.is_match(tag)) && !self.pinned.iter().any(|r| r.is_match(tag))
+    }
+
+    /// a hint to pin the tag when it is mutable
+    pub fn warning(&self, tag: &str) -> Option<String> {
+        match self.is_mutable(tag) {
+            true => Some(format!(
+                "⚠ {} is a mutable tag, pin a full version or a digest for reproducible deployments",
+                tag
+            )),
+            false => None,
+        }
+    }
+}
+
+fn compile<S: AsRef<str>>(patterns: &[S]) -> Result<Vec<Regex>, String> {
+    patterns
+        .iter()
+        .map(|p| {
+            Regex::new(p.as_ref()).map_err(|e| format!("invalid tag pattern {}: {}", p.as_ref(), e))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TagPolicy;
+
+    #[test]
+    fn test_is_mutable() {
+        let policy = TagPolicy::default();
+        let input: Vec<(&str, bool)> = vec![
+            ("latest", true),
+            ("stable-alpine", true),
+            ("alpine-latest", true),
+            ("1", true),
+            ("1.25", true),
+            ("v1.25-alpine", true),
+            ("1.25.3", false),
+            ("1.25.3-alpine", false),
+            ("20240101", false),
+            ("sha-4f2a1c", false),
+        ];
+
+        for i in input {
+            assert_eq!(policy.is_mutable(i.0), i.1, "{}", i.0);
+        }
+    }
+
+    #[test]
+    fn test_configured_patterns() {
+        let policy = TagPolicy::new(Some(&["^dev$".to_string()]), &[]).unwrap();
+        assert!(policy.is_mutable("dev"));
+        assert!(!policy.is_mutable("latest"));
+
+        let policy = TagPolicy::new(None, &["^1\\.25$".to_string()]).unwrap();
+        assert!(!policy.is_mutable("1.25"));
+        assert!(policy.is_mutable("1.24"));
+
+        assert!(TagPolicy::new(Some(&["(".to_string()]), &[]).is_err());
+    }
+}
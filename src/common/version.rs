@@ -0,0 +1,243 @@
+use std::cmp::Ordering;
+
+use regex::Regex;
+
+/// a tag like v1.25.3-alpine split into its parts
+#[derive(Debug, PartialEq)]
+struct Version<'a> {
+    prefix: &'a str,
+    major: u64,
+    minor: u64,
+    patch: Option<u64>,
+    suffix: &'a str,
+}
+
+impl<'a> Version<'a> {
+    fn parse(tag: &'a str) -> Option<Self> {
+        lazy_static::lazy_static! {
+            static ref REGEX: Regex = Regex::new(r"^(v?)(\d+)\.(\d+)(?:\.(\d+))?(.*)$").unwrap();
+        }
+        let caps = REGEX.captures(tag)?;
+        Some(Self {
+            prefix: caps.get(1)?.as_str(),
+            major: caps[2].parse().ok()?,
+            minor: caps[3].parse().ok()?,
+            patch: caps.get(4).and_then(|p| p.as_str().parse().ok()),
+            suffix: caps.get(5).map(|s| s.as_str()).unwrap_or_default(),
+        })
+    }
+}
+
+/// the variant family of a tag, e.g. alpine for 1.25.3-alpine and an empty one for latest,
+/// a tag matching one of the patterns belongs to the family named by the first of them
+pub fn variant<'a>(tag: &'a str, patterns: &'a [Regex]) -> &'a str {
+    if let Some(pattern) = patterns.iter().find(|p| p.is_match(tag)) {
+        return pattern.as_str();
+    }
+    match Version::parse(tag) {
+        Some(version) => version.suffix.trim_start_matches('-'),
+        None => tag.trim_start_matches("latest").trim_start_matches('-'),
+    }
+}
+
+/// the tag with its last number changed by delta, e.g. 1.25.4 for 1.25.3 and 1,
+/// leading zeros are kept like in 2024.05
+pub fn bump(tag: &str, delta: i64) -> Option<String> {
+    lazy_static::lazy_static! {
+        static ref REGEX: Regex = Regex::new(r"^(.*?)(\d+)(\D*)$").unwrap();
+    }
+    let caps = REGEX.captures(tag)?;
+    let number = &caps[2];
+    let bumped = number.parse::<i64>().ok()?.checked_add(delta)?;
+    if bumped < 0 {
+        return None;
+    }
+    Some(format!(
+        "{}{:0width$}{}",
+        &caps[1],
+        bumped,
+        &caps[3],
+        width = number.len()
+    ))
+}
+
+/// the tag with the highest patch of the same major.minor and variant as the current tag,
+/// e.g. 1.25.4-alpine for 1.25-alpine
+pub fn newest_patch<'a>(current: &str, tags: &[&'a str]) -> Option<&'a str> {
+    let current = Version::parse(current)?;
+    tags.iter()
+        .filter_map(|&tag| Some((tag, Version::parse(tag)?)))
+        .filter(|(_, v)| {
+            v.prefix == current.prefix
+                && v.major == current.major
+                && v.minor == current.minor
+                && v.suffix == current.suffix
+                && v.patch.is_some()
+        })
+        .max_by_key(|(_, v)| v.patch)
+        .map(|(tag, _)| tag)
+}
+
+/// the tag with the highest version of the same form and variant as the current tag,
+/// e.g. 1.27.0-alpine for 1.25.3-alpine and 1.27 for 1.25
+pub fn newest_version<'a>(current: &str, tags: &[&'a str]) -> Option<&'a str> {
+    let current = Version::parse(current)?;
+    tags.iter()
+        .filter_map(|&tag| Some((tag, Version::parse(tag)?)))
+        .filter(|(_, v)| {
+            v.prefix == current.prefix
+                && v.suffix == current.suffix
+                && v.patch.is_some() == current.patch.is_some()
+        })
+        .max_by_key(|(_, v)| (v.major, v.minor, v.patch))
+        .map(|(tag, _)| tag)
+}
+
+/// whether the tag has a higher version than the current one
+pub fn is_newer(tag: &str, current: &str) -> bool {
+    match (Version::parse(tag), Version::parse(current)) {
+        (Some(tag), Some(current)) => {
+            (tag.major, tag.minor, tag.patch) > (current.major, current.minor, current.patch)
+        }
+        _ => false,
+    }
+}
+
+/// orders tags by their version, the newest first and the plain one before its variants,
+/// tags without a version follow in alphabetical order
+pub fn newest_first(a: &str, b: &str) -> Ordering {
+    match (Version::parse(a), Version::parse(b)) {
+        (Some(va), Some(vb)) => (vb.major, vb.minor, vb.patch)
+            .cmp(&(va.major, va.minor, va.patch))
+            .then_with(|| va.suffix.cmp(vb.suffix))
+            .then_with(|| a.cmp(b)),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => a.cmp(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_variant() {
+        let patterns = vec![regex::Regex::new("alpine").unwrap()];
+        let input: Vec<(&str, &[regex::Regex], &str)> = vec![
+            ("1.25.3", &[], ""),
+            ("latest", &[], ""),
+            ("1.25.3-alpine3.19", &[], "alpine3.19"),
+            ("v2-slim", &[], "v2-slim"),
+            ("2.1-slim", &[], "slim"),
+            ("latest-alpine", &[], "alpine"),
+            ("stable", &[], "stable"),
+            ("1.25.3-alpine3.19", &patterns, "alpine"),
+            ("alpine", &patterns, "alpine"),
+            ("1.25.3", &patterns, ""),
+        ];
+
+        for i in input {
+            assert_eq!(super::variant(i.0, i.1), i.2, "{}", i.0);
+        }
+    }
+
+    #[test]
+    fn test_bump() {
+        let input: Vec<(&str, i64, Option<&str>)> = vec![
+            ("1.25.3", 1, Some("1.25.4")),
+            ("1.25.3", -1, Some("1.25.2")),
+            ("1.25.9", 1, Some("1.25.10")),
+            ("1.25.3-alpine", 1, Some("1.25.4-alpine")),
+            ("2024.05", 1, Some("2024.06")),
+            ("1.25.0", -1, None),
+            ("latest", 1, None),
+        ];
+
+        for i in input {
+            assert_eq!(super::bump(i.0, i.1).as_deref(), i.2, "{}", i.0);
+        }
+    }
+
+    #[test]
+    fn test_newest_patch() {
+        let tags = vec![
+            "1.25.10",
+            "1.25.9",
+            "1.25.11-alpine",
+            "1.26.0",
+            "1.25",
+            "v1.25.12",
+            "latest",
+        ];
+        let input: Vec<(&str, Option<&str>)> = vec![
+            ("1.25", Some("1.25.10")),
+            ("1.25.1", Some("1.25.10")),
+            ("1.25-alpine", Some("1.25.11-alpine")),
+            ("v1.25.0", Some("v1.25.12")),
+            ("1.24", None),
+            ("latest", None),
+        ];
+
+        for i in input {
+            assert_eq!(super::newest_patch(i.0, &tags), i.1, "{}", i.0);
+        }
+    }
+
+    #[test]
+    fn test_newest_version() {
+        let tags = vec![
+            "1.25.3",
+            "1.27.0",
+            "1.26",
+            "1.27.1-alpine",
+            "2.0.0rc1",
+            "latest",
+        ];
+        let input: Vec<(&str, Option<&str>)> = vec![
+            ("1.25.3", Some("1.27.0")),
+            ("1.25", Some("1.26")),
+            ("1.25.3-alpine", Some("1.27.1-alpine")),
+            ("latest", None),
+        ];
+
+        for i in input {
+            assert_eq!(super::newest_version(i.0, &tags), i.1, "{}", i.0);
+        }
+        assert!(super::is_newer("1.27.0", "1.25.3"));
+        assert!(!super::is_newer("1.25.3", "1.25.3"));
+        assert!(!super::is_newer("latest", "1.25.3"));
+    }
+
+    #[test]
+    fn test_newest_first() {
+        let mut tags = vec![
+            "latest",
+            "1.25.3",
+            "20240301",
+            "v1.27.0",
+            "1.25.3-alpine",
+            "1.25",
+            "stable",
+            "1.26.1-alpine",
+            "1.9.12",
+            "alpine",
+            "1.25.10",
+        ];
+        tags.sort_by(|a, b| super::newest_first(a, b));
+        assert_eq!(
+            tags,
+            vec![
+                "v1.27.0",
+                "1.26.1-alpine",
+                "1.25.10",
+                "1.25.3",
+                "1.25.3-alpine",
+                "1.25",
+                "1.9.12",
+                "20240301",
+                "alpine",
+                "latest",
+                "stable",
+            ]
+        );
+    }
+}
@@ -0,0 +1,276 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::common::tag_policy::TagPolicy;
+
+/// the name of the config file of a project, next to the compose file or in a parent folder
+const PROJECT_FILE: &str = ".reel-moby.toml";
+
+/// the keys of the config file, others are most likely typos
+const KEYS: [&str; 12] = [
+    "stale_days",
+    "page_size",
+    "namespaces",
+    "mutable_tags",
+    "pinned_tags",
+    "variants",
+    "backup",
+    "max_connections",
+    "sort_ties",
+    "pinned_repos",
+    "locale",
+    "vim_keys",
+];
+
+/// settings from the config file of the user
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(default)]
+pub struct Config {
+    /// mark images whose tag was last updated more than this many days ago
+    pub stale_days: Option<i64>,
+    /// tags per page to ask registries for, limited to what each registry allows
+    pub page_size: Option<usize>,
+    /// namespaces to cycle through, e.g. library and bitnami
+    pub namespaces: Vec<String>,
+    /// patterns of tags which move to newer images, replacing the default ones like latest
+    pub mutable_tags: Option<Vec<String>>,
+    /// patterns of tags which are not warned about, even when they look mutable
+    pub pinned_tags: Vec<String>,
+    /// patterns naming variant families, e.g. alpine, instead of the suffix of the tags
+    pub variants: Vec<String>,
+    /// copy the compose file before saving over it
+    pub backup: bool,
+    /// fetches from registries at the same time, overridden by --max-connections
+    pub max_connections: Option<usize>,
+    /// the order by name of tags updated at the same time
+    pub sort_ties: Option<crate::repository::TieOrder>,
+    /// repositories or services which are left out when updating all lines or checking for stale tags
+    pub pinned_repos: Vec<String>,
+    /// the language of relative times like 3 Days ago
+    pub locale: crate::common::display_duration_ext::Locale,
+    /// move in the lists with j, k, g and G too, which then don't filter the tags
+    pub vim_keys: bool,
+    /// the compiled patterns of mutable and pinned tags
+    #[serde(skip)]
+    pub tag_policy: TagPolicy,
+}
+
+impl Config {
+    /// the config file in the platform specific config directory, e.g. ~/.config/reel-moby/config.toml
+    pub fn default_path() -> Option<PathBuf> {
+        let dirs = directories::ProjectDirs::from("", "", "reel-moby")?;
+        Some(dirs.config_dir().join("config.toml"))
+    }
+
+    /// loads the config of the user with the config of the project in the folder over it,
+    /// missing files result in the default config
+    pub fn load(folder: &Path) -> Result<Self, String> {
+        let files: Vec<PathBuf> = Self::default_path()
+            .into_iter()
+            .chain(project_file(folder))
+            .filter(|path| path.exists())
+            .collect();
+
+        let mut merged = toml::value::Table::new();
+        for path in &files {
+            let content = read(path)?;
+            Self::parse(&content)
+                .map_err(|e| format!("invalid config {}: {}", path.display(), e))?;
+            if let Ok(toml::Value::Table(table)) = toml::from_str(&content) {
+                merged.extend(table);
+            }
+        }
+        Self::parse(&toml::Value::Table(merged).to_string())
+    }
+
+    fn parse(content: &str) -> Result<Self, String> {
+        let value: toml::Value = toml::from_str(content).map_err(describe)?;
+        if let Some(table) = value.as_table() {
+            if let Some(key) = table.keys().find(|k| !KEYS.contains(&k.as_str())) {
+                return Err(format!(
+                    "{}: unknown key, known keys are {}",
+                    key,
+                    KEYS.join(", ")
+                ));
+            }
+        }
+
+        let mut config: Self = toml::from_str(content).map_err(describe)?;
+        config.validate()?;
+        config.tag_policy = TagPolicy::new(config.mutable_tags.as_deref(), &config.pinned_tags)?;
+        Ok(config)
+    }
+
+    /// checks the values which have the right type but can't be used
+    fn validate(&self) -> Result<(), String> {
+        if let Some(days) = self.stale_days.filter(|&d| d <= 0) {
+            return Err(format!(
+                "stale_days: {} is not a positive number of days",
+                days
+            ));
+        }
+        if let Some(size) = self.page_size.filter(|&s| s == 0) {
+            return Err(format!(
+                "page_size: {} is not a positive number of tags",
+                size
+            ));
+        }
+        if let Some(max) = self.max_connections.filter(|&m| m == 0) {
+            return Err(format!(
+                "max_connections: {} is not a positive number of connections",
+                max
+            ));
+        }
+        if let Some(namespace) = self
+            .namespaces
+            .iter()
+            .find(|n| n.is_empty() || n.contains('/'))
+        {
+            return Err(format!("namespaces: '{}' is not a namespace", namespace));
+        }
+
+        let mutable = self.mutable_tags.clone().unwrap_or_default();
+        for (key, patterns) in [
+            ("mutable_tags", &mutable),
+            ("pinned_tags", &self.pinned_tags),
+            ("variants", &self.variants),
+        ] {
+            for pattern in patterns {
+                if let Err(e) = regex::Regex::new(pattern) {
+                    return Err(format!(
+                        "{}: '{}' is not a valid pattern, {}",
+                        key, pattern, e
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn read(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("could not read {}: {}", path.display(), e))
+}
+
+/// the closest config of the project, looking in the parent folders up to the root of the git repository
+fn project_file(folder: &Path) -> Option<PathBuf> {
+    let folder = match folder.as_os_str().is_empty() {
+        true => std::env::current_dir().ok()?,
+        false => folder.canonicalize().ok()?,
+    };
+    for dir in folder.ancestors() {
+        let path = dir.join(PROJECT_FILE);
+        if path.exists() {
+            return Some(path);
+        }
+        if dir.join(".git").exists() {
+            break;
+        }
+    }
+    None
+}
+
+/// moves the key of a toml error to the front, e.g. stale_days: invalid type ... (line 1, column 14)
+fn describe(e: toml::de::Error) -> String {
+    lazy_static::lazy_static! {
+        static ref REGEX: regex::Regex =
+            regex::Regex::new(r"^(?s)(.*) for key `([^`]+)`(?: at line \d+ column \d+)?$").unwrap();
+    }
+    let message = format!("{}", e);
+    let position = match e.line_col() {
+        Some((line, column)) => format!(" (line {}, column {})", line + 1, column + 1),
+        None => String::new(),
+    };
+    match REGEX.captures(&message) {
+        Some(caps) => format!("{}: {}{}", &caps[2], &caps[1], position),
+        None => message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::Config;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Config::parse("").unwrap().stale_days, None);
+        assert_eq!(
+            Config::parse("stale_days = 180").unwrap().stale_days,
+            Some(180)
+        );
+        assert_eq!(
+            Config::parse("namespaces = [\"library\", \"bitnami\"]")
+                .unwrap()
+                .namespaces,
+            vec!["library", "bitnami"]
+        );
+        assert!(Config::parse("pinned_tags = [\"^lts$\"]")
+            .unwrap()
+            .tag_policy
+            .warning("lts")
+            .is_none());
+        assert_eq!(
+            Config::parse("sort_ties = \"descending\"")
+                .unwrap()
+                .sort_ties,
+            Some(crate::repository::TieOrder::Descending)
+        );
+        assert_eq!(
+            Config::parse("locale = \"german\"").unwrap().locale,
+            crate::common::display_duration_ext::Locale::German
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let input: Vec<(&str, &str)> = vec![
+            (
+                "stale_days = \"long\"",
+                "stale_days: invalid type: string \"long\", expected i64 (line 1, column 14)",
+            ),
+            ("stale_days = -3", "stale_days: -3 is not a positive number of days"),
+            ("page_size = 0", "page_size: 0 is not a positive number of tags"),
+            (
+                "namespaces = [\"library\", \"a/b\"]",
+                "namespaces: 'a/b' is not a namespace",
+            ),
+            (
+                "stale_day = 3",
+                "stale_day: unknown key, known keys are stale_days, page_size, namespaces, mutable_tags, pinned_tags, variants, backup, max_connections, sort_ties, pinned_repos, locale, vim_keys",
+            ),
+        ];
+
+        for i in input {
+            assert_eq!(Config::parse(i.0).unwrap_err(), i.1);
+        }
+        assert!(Config::parse("mutable_tags = [\"[\"]")
+            .unwrap_err()
+            .starts_with("mutable_tags: '[' is not a valid pattern"));
+    }
+
+    #[test]
+    fn test_project_file() {
+        let root = std::env::temp_dir().join("reel-moby-project-test");
+        let compose = root.join("deploy").join("web");
+        fs::create_dir_all(&compose).unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        assert_eq!(super::project_file(&compose), None);
+
+        fs::write(root.join(".reel-moby.toml"), "stale_days = 30").unwrap();
+        assert_eq!(
+            super::project_file(&compose),
+            Some(root.canonicalize().unwrap().join(".reel-moby.toml"))
+        );
+        let config = Config::load(&compose).unwrap();
+        assert_eq!(config.stale_days, Some(30));
+
+        fs::write(compose.join(".reel-moby.toml"), "page_size = 10").unwrap();
+        let config = Config::load(&compose).unwrap();
+        assert_eq!(config.page_size, Some(10));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,29 @@
+//! fetching the tags of docker images from docker hub and other registries,
+//! the same way the reel-moby ui does it
+//!
+//! the tags arrive page by page, the following pages are fetched with [`repository::Repo::next_page`]:
+//!
+//! ```no_run
+//! use reel_moby::repository::{self, Repo, Settings};
+//!
+//! // nginx is library/nginx on docker hub
+//! let name = repository::check_repo("nginx:1.25").unwrap();
+//! let settings = Settings::default();
+//!
+//! let mut page = Repo::new(&name, &settings).unwrap();
+//! loop {
+//!     for tag in page.get_tags() {
+//!         println!("{}", tag.get_name_with_details());
+//!     }
+//!     match page.next_page() {
+//!         Some(next) => page = next.unwrap(),
+//!         None => break,
+//!     }
+//! }
+//! ```
+
+pub mod common;
+pub mod repo;
+pub mod repository;
+
+pub use repository::{check_repo, Error, Repo, Settings, Tag};
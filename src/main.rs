@@ -1,26 +1,254 @@
 use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
 use structopt::StructOpt;
 
-mod common;
-mod repo;
-mod repository;
+use reel_moby::{common, repo, repository};
+
+mod cli;
+mod config;
 mod ui;
 mod widget;
 
 /// helps you searching or updating tags of your used docker images
 #[derive(StructOpt, Debug)]
 pub struct Opt {
-    /// A custom path to a docker-compose file
-    #[structopt(short, long, parse(from_os_str))]
-    file: Option<PathBuf>,
+    /// A custom path to a docker-compose file, given several times the files are merged in order
+    #[structopt(short, long, global = true, parse(from_os_str), number_of_values = 1)]
+    file: Vec<PathBuf>,
+
+    /// Also load the override file next to the compose file, e.g. docker-compose.override.yml
+    #[structopt(long, global = true)]
+    with_override: bool,
 
     /// Give a Repository identifier, e.g. library/nginx
     #[structopt(short, long, parse(from_str))]
     repo: Option<String>,
+
+    /// Only show tags updated since a date, e.g. 2024-01-01 or 30d
+    #[structopt(long, parse(try_from_str = common::date::parse_since))]
+    since: Option<DateTime<Utc>>,
+
+    /// Hide the details pane and show the details in a single line instead
+    #[structopt(long)]
+    compact: bool,
+
+    /// Print the tags of --repo as json instead of starting the ui, errors are printed as json to stderr
+    #[structopt(long)]
+    json: bool,
+
+    /// Print the images of the compose file with the newest version of their tag as json instead of starting the ui,
+    /// exits with 1 when an image is outdated
+    #[structopt(long)]
+    check: bool,
+
+    /// Print the requests to registries instead of sending them, works with --json, --check and apply
+    #[structopt(long, global = true)]
+    dry_run: bool,
+
+    /// Number the tags, don't rely on colors and describe the selection in the info line for screen readers
+    #[structopt(long)]
+    accessible: bool,
+
+    /// Browse tags without changing or saving the docker-compose file
+    #[structopt(long)]
+    read_only: bool,
+
+    /// Copy the compose file before saving over it, like backup in the config
+    #[structopt(long, global = true)]
+    backup: bool,
+
+    /// Update all lines with the same repository when selecting a tag, after a confirmation
+    #[structopt(long)]
+    update_all: bool,
+
+    /// Refresh the tags after this many seconds without a key press
+    #[structopt(long)]
+    refresh_idle: Option<u64>,
+
+    /// Add a query parameter to the tag list requests, e.g. --query name=1.2, can be repeated, the ECR gallery ignores them
+    #[structopt(long, number_of_values = 1, parse(try_from_str = repository::parse_query_param))]
+    query: Vec<(String, String)>,
+
+    /// Log in to registries with this user and the password in REEL_MOBY_PASSWORD instead of the docker config
+    #[structopt(long, env = "REEL_MOBY_USERNAME")]
+    username: Option<String>,
+
+    /// An access token used as the password of --username, e.g. for private Docker Hub repositories
+    #[structopt(long, env = "DOCKER_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+
+    /// The registry --username and its password are sent to, other registries use the docker config
+    #[structopt(long, default_value = repository::DEFAULT_REGISTRY_HOST)]
+    registry_host: String,
+
+    /// Fetch from registries with at most this many connections at the same time, by default 4
+    #[structopt(long)]
+    max_connections: Option<usize>,
+
+    /// Give up on registries which don't answer a request within this many seconds
+    #[structopt(long, global = true, default_value = "10")]
+    timeout: u64,
+
+    /// Send a request this many times again when the connection failed or timed out
+    #[structopt(long, global = true, default_value = "3")]
+    retries: usize,
+
+    /// Send all requests through this proxy instead of the ones in HTTP_PROXY, HTTPS_PROXY and NO_PROXY
+    #[structopt(long, global = true, parse(try_from_str = repository::parse_proxy))]
+    proxy: Option<String>,
+
+    /// Show the cached tags of repositories fetched within this many minutes, 0 disables the cache
+    #[structopt(long, default_value = "60")]
+    cache_ttl: u32,
+
+    /// Fetch all tags from the registries instead of showing cached ones
+    #[structopt(long)]
+    no_cache: bool,
+
+    /// Abort registry responses bigger than this many MiB
+    #[structopt(long, default_value = "8")]
+    max_body_size: u64,
+
+    /// Write the session to this file when pressing C-x, by default reel-moby-session.json
+    #[structopt(long, parse(from_os_str))]
+    export_session: Option<PathBuf>,
+
+    /// Show an exported session instead of fetching tags
+    #[structopt(long, parse(from_os_str))]
+    import_session: Option<PathBuf>,
+
+    /// The platform shown instead of the host one, e.g. linux/arm/v6
+    #[structopt(long)]
+    platform: Option<widget::details::Platform>,
+
+    /// Only list tags with an image for this platform, e.g. linux/arm64
+    #[structopt(long)]
+    arch: Option<widget::details::Platform>,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
+
+    #[structopt(skip)]
+    session: Option<ui::session::Session>,
+
+    #[structopt(skip)]
+    config: config::Config,
+}
+
+#[derive(StructOpt, Debug)]
+enum Command {
+    /// Set the tag of a service and save the file without starting the ui
+    Apply {
+        /// The service whose image is changed
+        #[structopt(long)]
+        service: String,
+
+        /// The new tag of the image
+        #[structopt(long)]
+        tag: String,
+
+        /// Don't check that the tag exists on the registry
+        #[structopt(long)]
+        no_verify: bool,
+    },
+}
+
+impl Opt {
+    /// the settings for requests to registries
+    fn settings(&self) -> repository::Settings {
+        repository::Settings {
+            max_body_size: self.max_body_size.saturating_mul(1024 * 1024),
+            page_size: self.config.page_size,
+            extra_query: self.query.clone(),
+            username: self.username.clone(),
+            password: self
+                .token
+                .clone()
+                .or_else(|| std::env::var("REEL_MOBY_PASSWORD").ok()),
+            registry_host: self.registry_host.clone(),
+            max_connections: self
+                .max_connections
+                .or(self.config.max_connections)
+                .unwrap_or(repository::DEFAULT_MAX_CONNECTIONS),
+            dry_run: self.dry_run,
+            tie_order: self.config.sort_ties.unwrap_or_default(),
+            timeout: std::time::Duration::from_secs(self.timeout),
+            retries: self.retries,
+            proxy: self.proxy.clone(),
+            wait_notice: None,
+        }
+    }
 }
 
 fn main() {
     //parse parameter
-    let opt = Opt::from_args();
+    let mut opt = Opt::from_args();
+
+    if let Some(path) = &opt.import_session {
+        match ui::session::Session::load(path) {
+            Ok(session) => {
+                if opt.file.is_empty() {
+                    opt.file = session.file.iter().cloned().collect();
+                }
+                opt.session = Some(session);
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // the config of the project is next to the compose file, flags override both configs
+    let folder = opt
+        .file
+        .first()
+        .and_then(|file| file.parent())
+        .unwrap_or_else(|| std::path::Path::new(""));
+    opt.config = match config::Config::load(folder) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    common::display_duration_ext::set_locale(opt.config.locale);
+
+    opt.repo = opt.repo.as_deref().map(repo::from_web_url);
+
+    if let Some(Command::Apply {
+        service,
+        tag,
+        no_verify,
+    }) = &opt.command
+    {
+        std::process::exit(cli::apply::run(&opt, service, tag, !no_verify));
+    }
+    if opt.json {
+        std::process::exit(cli::run(&opt));
+    }
+    if opt.check {
+        std::process::exit(cli::check::run(&opt));
+    }
+    if opt.dry_run {
+        eprintln!("--dry-run only works with --json, --check or apply");
+        std::process::exit(2);
+    }
+    // without the files the user asked for, another compose file would be discovered instead
+    opt.file = opt
+        .file
+        .iter()
+        .map(|file| match file.exists() {
+            true => file.clone(),
+            false => ui::pick_compose_file(file).unwrap_or_else(|| {
+                eprintln!(
+                    "{} does not exist and no other file was picked",
+                    file.display()
+                );
+                std::process::exit(1);
+            }),
+        })
+        .collect();
     ui::create_ui(&opt);
 }
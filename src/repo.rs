@@ -28,9 +28,10 @@ pub enum Repo {
 /// the first &str is the image tag
 /// it will be used to not change the identation
 /// the second &str will the the identifier for the image
+/// the Image= key of podman quadlet .container files matches as well
 pub fn match_yaml_image(input: &str) -> Result<(&str, &str), Error> {
     lazy_static::lazy_static! {
-        static ref REGEX: Regex = Regex::new(r"^( +image *: *)([a-z0-9\-\./:]+)").unwrap();
+        static ref REGEX: Regex = Regex::new(r"^( +image *: *|Image *= *)([a-z0-9\-\./:@]+)").unwrap();
     }
     let caps = match REGEX.captures(input) {
         Some(caps) => caps,
@@ -43,24 +44,25 @@ pub fn match_yaml_image(input: &str) -> Result<(&str, &str), Error> {
 /// takes the identifier and splits off the tag it exists
 pub fn split_tag_from_repo(input: &str) -> Result<(&str, &str), Error> {
     lazy_static::lazy_static! {
-        static ref REGEX: Regex = Regex::new(r"^([a-z0-9\./[^:]]*):?([a-z0-9._\-]*)").unwrap();
+        static ref TAG: Regex = Regex::new(r"^[a-z0-9._\-]*").unwrap();
     }
-    let (front, back) = match REGEX.captures(input) {
-        None => return Err(Error::MisformedInput),
-        Some(caps) => {
-            let front = match caps.get(1) {
-                None => return Err(Error::MisformedInput),
-                Some(cap) => cap.as_str(),
-            };
-            let back = match caps.get(2) {
-                None => "",
-                Some(cap) => cap.as_str(),
-            };
-            (front, back)
+    // an image pinned to a digest like nginx:1.25@sha256:… is only named by the part before
+    let input = input.split('@').next().unwrap_or(input);
+    // a colon before the last slash belongs to the port of a registry like localhost:5000
+    let name_start = input.rfind('/').map_or(0, |i| i + 1);
+    match input[name_start..].find(':') {
+        None => Ok((input, "")),
+        Some(i) => {
+            let (front, back) = (&input[..name_start + i], &input[name_start + i + 1..]);
+            Ok((front, TAG.find(back).map_or("", |m| m.as_str())))
         }
-    };
+    }
+}
 
-    Ok((front, back))
+/// whether the first part of a repo is the host of a registry, docker hub organizations
+/// can't contain a dot or a port, e.g. ghcr.io or localhost:5000
+pub fn is_registry_host(part: &str) -> bool {
+    part.contains('.') || part.contains(':') || part == "localhost"
 }
 
 /// takes an identifier and changes it to a Repo enum
@@ -93,14 +95,67 @@ pub fn split_repo_without_tag(repo: &str) -> Result<Repo, Error> {
                 )),
             }
         }
+        n if n > 3 => {
+            let regex = regex::Regex::new(r"[a-z0-9\.]+(/[a-z0-9]+){3,}").unwrap();
+            match regex.is_match(repo) {
+                false => Err(Error::MisformedInput),
+                true => Ok(Repo::WithServer(
+                    split_repo[0].into(),
+                    split_repo[1..n - 1].join("/"),
+                    split_repo[n - 1].into(),
+                )),
+            }
+        }
         _ => Err(Error::MisformedInput),
     }
 }
 
+/// the repo of a web page of docker hub or the ecr gallery copied from the browser, e.g.
+/// <https://hub.docker.com/_/nginx> is library/nginx, other input is returned as it is
+pub fn from_web_url(input: &str) -> String {
+    let input = input.trim();
+    let path = match input
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .split_once('/')
+    {
+        Some((host, path)) if host == "hub.docker.com" || host == "gallery.ecr.aws" => {
+            (host, path.split(['?', '#']).next().unwrap_or_default())
+        }
+        _ => return input.to_string(),
+    };
+    let parts: Vec<&str> = path.1.split('/').filter(|p| !p.is_empty()).collect();
+
+    match (path.0, parts.as_slice()) {
+        ("hub.docker.com", ["_", name, ..]) => format!("library/{}", name),
+        ("hub.docker.com", ["r", namespace, name, ..]) => format!("{}/{}", namespace, name),
+        // the page of an image also contains its tag and digest
+        ("hub.docker.com", ["layers", namespace, name, ..]) => format!("{}/{}", namespace, name),
+        ("gallery.ecr.aws", [alias, name, ..]) => format!("public.ecr.aws/{}/{}", alias, name),
+        _ => input.to_string(),
+    }
+}
+
+/// the repo in the namespace following its current one in the list, e.g. bitnami/redis after library/redis
+pub fn next_namespace(repo: &str, namespaces: &[String]) -> Option<String> {
+    let (namespace, name) = match split_repo_without_tag(repo).ok()? {
+        Repo::Project(name) => (String::from("library"), name),
+        Repo::WithOrga(namespace, name) => (namespace, name),
+        Repo::WithServer(..) => return None,
+    };
+    let next = match namespaces.iter().position(|n| *n == namespace) {
+        Some(i) => namespaces.get(i + 1).unwrap_or(&namespaces[0]),
+        None => namespaces.first()?,
+    };
+    Some(format!("{}/{}", next, name))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::repo::{Error, Repo};
 
+    type Split<'a> = Result<(&'a str, &'a str), Error>;
+
     #[test]
     fn test_split_repo_without_tag() {
         let input: Vec<(&str, Result<Repo, Error>)> = vec![
@@ -119,6 +174,14 @@ mod tests {
                     "nginx".into(),
                 )),
             ),
+            (
+                "public.ecr.aws/docker/library/nginx",
+                Ok(Repo::WithServer(
+                    "public.ecr.aws".into(),
+                    "docker/library".into(),
+                    "nginx".into(),
+                )),
+            ),
             (
                 "te-st/test-hypen",
                 Ok(Repo::WithOrga("te-st".into(), "test-hypen".into())),
@@ -136,7 +199,7 @@ mod tests {
 
     #[test]
     fn test_match_yaml_image() {
-        let input: Vec<(&str, Result<(&str, &str), Error>)> = vec![
+        let input: Vec<(&str, Split)> = vec![
             ("", Err(Error::NoTagFound)),
             ("version: '2'", Err(Error::NoTagFound)),
             ("image: ", Err(Error::NoTagFound)),
@@ -150,6 +213,17 @@ mod tests {
             ("  image: nginx # comment", Ok(("  image: ", "nginx"))),
             ("  image: test-hyphen", Ok(("  image: ", "test-hyphen"))),
             ("  image: test.dot", Ok(("  image: ", "test.dot"))),
+            (
+                "Image=docker.io/library/nginx:1.25",
+                Ok(("Image=", "docker.io/library/nginx:1.25")),
+            ),
+            ("Image = nginx", Ok(("Image = ", "nginx"))),
+            (
+                "  image: nginx@sha256:abc",
+                Ok(("  image: ", "nginx@sha256:abc")),
+            ),
+            ("#Image=nginx", Err(Error::NoTagFound)),
+            ("ContainerName=nginx", Err(Error::NoTagFound)),
         ];
 
         for i in input {
@@ -159,7 +233,7 @@ mod tests {
 
     #[test]
     fn test_split_tag_from_repo() {
-        let input: Vec<(&str, Result<(&str, &str), super::Error>)> = vec![
+        let input: Vec<(&str, Split)> = vec![
             ("nginx", Ok(("nginx", ""))),
             ("library/nginx", Ok(("library/nginx", ""))),
             ("ghcr.io/library/nginx", Ok(("ghcr.io/library/nginx", ""))),
@@ -172,10 +246,70 @@ mod tests {
                 "woodpeckerci/woodpecker-server",
                 Ok(("woodpeckerci/woodpecker-server", "")),
             ),
+            ("localhost:5000/app", Ok(("localhost:5000/app", ""))),
+            ("localhost:5000/app:1.0", Ok(("localhost:5000/app", "1.0"))),
+            ("quay.io/org/image:v2", Ok(("quay.io/org/image", "v2"))),
+            ("nginx@sha256:abc", Ok(("nginx", ""))),
+            ("nginx:1.25@sha256:abc", Ok(("nginx", "1.25"))),
         ];
 
         for i in input {
             assert_eq!(super::split_tag_from_repo(i.0), i.1);
         }
     }
+
+    #[test]
+    fn test_next_namespace() {
+        let namespaces = vec![String::from("library"), String::from("bitnami")];
+        let input: Vec<(&str, Option<&str>)> = vec![
+            ("redis", Some("bitnami/redis")),
+            ("library/redis", Some("bitnami/redis")),
+            ("bitnami/redis", Some("library/redis")),
+            ("someone/redis", Some("library/redis")),
+            ("ghcr.io/someone/redis", None),
+        ];
+
+        for i in input {
+            assert_eq!(
+                super::next_namespace(i.0, &namespaces).as_deref(),
+                i.1,
+                "{}",
+                i.0
+            );
+        }
+        assert_eq!(super::next_namespace("redis", &[]), None);
+    }
+
+    #[test]
+    fn test_from_web_url() {
+        let input: Vec<(&str, &str)> = vec![
+            (
+                "https://hub.docker.com/r/grafana/grafana",
+                "grafana/grafana",
+            ),
+            (
+                "https://hub.docker.com/r/grafana/grafana/tags?name=10",
+                "grafana/grafana",
+            ),
+            ("https://hub.docker.com/_/nginx", "library/nginx"),
+            ("hub.docker.com/_/nginx/?tab=tags#top", "library/nginx"),
+            (
+                "https://hub.docker.com/layers/library/nginx/1.25/images/sha256-abc",
+                "library/nginx",
+            ),
+            (
+                "https://gallery.ecr.aws/nginx/nginx",
+                "public.ecr.aws/nginx/nginx",
+            ),
+            (
+                "https://hub.docker.com/search?q=nginx",
+                "https://hub.docker.com/search?q=nginx",
+            ),
+            (" library/nginx ", "library/nginx"),
+        ];
+
+        for i in input {
+            assert_eq!(super::from_web_url(i.0), i.1, "{}", i.0);
+        }
+    }
 }
@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::common::external;
+use crate::repository::Settings;
+
+/// the key the docker cli uses for docker hub
+const DOCKER_HUB_SERVER: &str = "https://index.docker.io/v1/";
+
+/// time a credential helper gets to answer
+const HELPER_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Deserialize, Default)]
+struct DockerConfig {
+    #[serde(default)]
+    auths: HashMap<String, AuthEntry>,
+    #[serde(rename(deserialize = "credsStore"))]
+    creds_store: Option<String>,
+    #[serde(rename(deserialize = "credHelpers"), default)]
+    cred_helpers: HashMap<String, String>,
+}
+
+#[derive(Deserialize, Default)]
+struct AuthEntry {
+    auth: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct HelperResponse {
+    #[serde(rename(deserialize = "Username"))]
+    username: String,
+    #[serde(rename(deserialize = "Secret"))]
+    secret: String,
+}
+
+impl Credentials {
+    /// credentials given with --username and REEL_MOBY_PASSWORD win over the ones of the docker config,
+    /// they are only sent to the registry of --registry-host
+    pub fn find(registry: &str, settings: &Settings) -> Option<Self> {
+        if let (Some(username), Some(password)) = (&settings.username, &settings.password) {
+            if normalize(registry) == normalize(&settings.registry_host) {
+                return Some(Self {
+                    username: username.clone(),
+                    password: password.clone(),
+                });
+            }
+        }
+        Self::from_docker_config(registry)
+    }
+
+    /// the value of a basic authorization header
+    pub fn basic_auth(&self) -> String {
+        format!(
+            "Basic {}",
+            base64::encode(format!("{}:{}", self.username, self.password))
+        )
+    }
+
+    /// looks up the credentials for a registry like docker.io the same way the docker cli does
+    pub fn from_docker_config(registry: &str) -> Option<Self> {
+        let content = std::fs::read_to_string(config_path()?).ok()?;
+        let config: DockerConfig = serde_json::from_str(&content).ok()?;
+        config.find(registry)
+    }
+}
+
+impl DockerConfig {
+    fn find(&self, registry: &str) -> Option<Credentials> {
+        let registry = normalize(registry);
+
+        // a helper for this registry wins over everything else
+        if let Some((server, helper)) = self
+            .cred_helpers
+            .iter()
+            .find(|(server, _)| normalize(server) == registry)
+        {
+            return from_helper(helper, server);
+        }
+
+        if let Some((server, entry)) = self
+            .auths
+            .iter()
+            .find(|(server, _)| normalize(server) == registry)
+        {
+            if let Some(credentials) = entry.decode() {
+                return Some(credentials);
+            }
+            // an empty entry means the secret lives in the credential store
+            if let Some(store) = &self.creds_store {
+                return from_helper(store, server);
+            }
+        }
+
+        match &self.creds_store {
+            Some(store) if registry == "docker.io" => from_helper(store, DOCKER_HUB_SERVER),
+            Some(store) => from_helper(store, &registry),
+            None => None,
+        }
+    }
+}
+
+impl AuthEntry {
+    fn decode(&self) -> Option<Credentials> {
+        if let (Some(username), Some(password)) = (&self.username, &self.password) {
+            return Some(Credentials {
+                username: username.clone(),
+                password: password.clone(),
+            });
+        }
+
+        let decoded = base64::decode(self.auth.as_ref()?.trim()).ok()?;
+        let decoded = String::from_utf8(decoded).ok()?;
+        let (username, password) = decoded.split_once(':')?;
+        Some(Credentials {
+            username: username.to_string(),
+            password: password.to_string(),
+        })
+    }
+}
+
+/// the config.json, which may be moved with DOCKER_CONFIG
+fn config_path() -> Option<PathBuf> {
+    match std::env::var_os("DOCKER_CONFIG") {
+        Some(dir) => Some(PathBuf::from(dir).join("config.json")),
+        None => {
+            let home = std::env::var_os("HOME")?;
+            Some(PathBuf::from(home).join(".docker").join("config.json"))
+        }
+    }
+}
+
+/// reduces a server entry like https://index.docker.io/v1/ to a registry host
+fn normalize(server: &str) -> String {
+    let host = server
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .split('/')
+        .next()
+        .unwrap_or_default();
+
+    match host {
+        "index.docker.io" | "registry-1.docker.io" | "registry.hub.docker.com" => {
+            String::from("docker.io")
+        }
+        host => host.to_string(),
+    }
+}
+
+/// asks a credential helper like docker-credential-desktop for the secret
+fn from_helper(helper: &str, server: &str) -> Option<Credentials> {
+    let mut cmd = Command::new(format!("docker-credential-{}", helper));
+    cmd.arg("get");
+    let output = external::output_with_timeout(&mut cmd, Some(server), HELPER_TIMEOUT).ok()?;
+    let response: HelperResponse = serde_json::from_str(&output).ok()?;
+    Some(Credentials {
+        username: response.username,
+        password: response.secret,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Credentials, DockerConfig};
+    use crate::repository::Settings;
+
+    #[test]
+    fn test_find_credentials() {
+        let config: DockerConfig = serde_json::from_str(
+            r#"{
+                "auths": {
+                    "https://index.docker.io/v1/": { "auth": "dXNlcjpzZWNyZXQ6cGFydA==" },
+                    "ghcr.io": { "username": "octo", "password": "token" }
+                },
+                "HttpHeaders": { "User-Agent": "Docker-Client" }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.find("docker.io"),
+            Some(Credentials {
+                username: "user".into(),
+                password: "secret:part".into()
+            })
+        );
+        assert_eq!(
+            config.find("ghcr.io"),
+            Some(Credentials {
+                username: "octo".into(),
+                password: "token".into()
+            })
+        );
+        assert_eq!(config.find("quay.io"), None);
+    }
+
+    #[test]
+    fn test_find_given_credentials() {
+        let settings = Settings {
+            username: Some("user".into()),
+            password: Some("secret".into()),
+            registry_host: "registry.invalid:5000".into(),
+            ..Settings::default()
+        };
+        assert_eq!(
+            Credentials::find("https://registry.invalid:5000", &settings),
+            Some(Credentials {
+                username: "user".into(),
+                password: "secret".into()
+            })
+        );
+        assert_eq!(Credentials::find("other.invalid", &settings), None);
+    }
+
+    #[test]
+    fn test_basic_auth() {
+        let credentials = Credentials {
+            username: "user".into(),
+            password: "secret".into(),
+        };
+        assert_eq!(credentials.basic_auth(), "Basic dXNlcjpzZWNyZXQ=");
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(super::normalize("https://index.docker.io/v1/"), "docker.io");
+        assert_eq!(super::normalize("registry-1.docker.io"), "docker.io");
+        assert_eq!(super::normalize("https://ghcr.io"), "ghcr.io");
+        assert_eq!(super::normalize("localhost:5000"), "localhost:5000");
+    }
+}
@@ -1,36 +1,56 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
 use serde::Deserialize;
 
-use crate::repository::Error;
+use crate::repository::credentials::Credentials;
+use crate::repository::{Capabilities, Error, Settings};
+
+const CAPABILITIES: Capabilities = Capabilities {
+    page_size: Some(("page_size", 100)),
+    newest_first: Some(("ordering", "last_updated")),
+};
 
+// unknown fields are ignored and everything besides the tag name may be missing,
+// so new or removed fields of the api don't break parsing
 #[derive(Deserialize, Debug, Clone)]
 struct ImageDetails {
-    architecture: String,
-    os: String,
+    architecture: Option<String>,
+    os: Option<String>,
     variant: Option<String>,
-    size: usize,
+    size: Option<usize>,
+    digest: Option<String>,
 }
 
 #[derive(Deserialize, Clone)]
 pub struct Images {
+    #[serde(default)]
     images: Vec<ImageDetails>,
     #[serde(rename(deserialize = "name"))]
     tag_name: String,
-    last_updated: String,
+    #[serde(default)]
+    last_updated: Option<String>,
+    /// the digest of the manifest list, or of the image for single platform tags
+    digest: Option<String>,
 }
 
 impl Images {
     pub fn convert(&self) -> super::Tag {
         super::Tag {
             name: self.tag_name.clone(),
-            last_updated: Some(self.last_updated.clone()),
+            last_updated: self.last_updated.clone(),
+            digest: self.digest.clone(),
+            // docker hub only tells when a tag was last pushed
+            created: None,
             details: self
                 .images
                 .iter()
                 .map(|d| super::TagDetails {
-                    arch: Some(d.architecture.clone()),
+                    arch: d.architecture.clone(),
                     variant: Some(d.variant.clone().unwrap_or_default()),
-                    os: Some(d.os.clone()),
-                    size: Some(d.size),
+                    os: d.os.clone(),
+                    size: d.size,
+                    digest: d.digest.clone(),
                 })
                 .collect(),
         }
@@ -41,25 +61,121 @@ impl Images {
 pub struct DockerHub {
     #[serde(rename(deserialize = "next"))]
     next_page: Option<String>,
+    #[serde(default)]
     results: Vec<Images>,
 }
 
+#[derive(Deserialize)]
+struct Login {
+    token: String,
+}
+
 impl DockerHub {
     /// fetches tag information with a repository name in the form of organization/repository or library/repository in the case of official images from docker
-    pub fn create_repo(repo: &str) -> Result<super::Repo, Error> {
-        let request = format!("https://hub.docker.com/v2/repositories/{}/tags", repo);
-        Self::with_url(&request)
+    /// uses the credentials of `docker login` when there are some
+    pub fn create_repo(repo: &str, settings: &Settings) -> Result<super::Repo, Error> {
+        let request = CAPABILITIES.url(
+            &format!("https://hub.docker.com/v2/repositories/{}/tags", repo),
+            settings,
+        );
+        // the next pages keep the parameters of the first one
+        let (auth, warning) = Self::auth(settings);
+        let mut repo = Self::with_url(&request, auth, settings)?;
+        repo.warning = warning;
+        Ok(repo)
     }
 
-    /// fetches tag information from a url
-    pub fn with_url(url: &str) -> Result<super::Repo, Error> {
-        let response = match reqwest::blocking::get(url) {
-            Ok(result) => result,
-            Err(e) => return Err(Error::Fetching(format!("reqwest error: {}", e))),
+    /// fetches the information of a single tag
+    pub fn fetch_tag(repo: &str, tag: &str, settings: &Settings) -> Result<super::Tag, Error> {
+        let url = format!(
+            "https://hub.docker.com/v2/repositories/{}/tags/{}",
+            repo, tag
+        );
+        let mut request = super::client(settings).get(&url);
+        if let (Some(auth), _) = Self::auth(settings) {
+            request = request.header(reqwest::header::AUTHORIZATION, auth);
+        }
+        let body = super::fetch_text(request, &format!("{}:{}", repo, tag), settings)?;
+        match serde_json::from_str::<Images>(&body) {
+            Ok(image) => Ok(image.convert()),
+            Err(e) => Err(Error::Converting(format!("invalid json: {}", e))),
+        }
+    }
+
+    /// the authorization header for the credentials of `docker login`,
+    /// after a failed login the tags are fetched anonymously with a warning
+    fn auth(settings: &Settings) -> (Option<String>, Option<String>) {
+        let credentials = match Credentials::find("docker.io", settings) {
+            Some(credentials) => credentials,
+            None => return (None, None),
+        };
+        match Self::login(&credentials, settings) {
+            Ok(token) => (Some(format!("JWT {}", token)), None),
+            Err(e) => (
+                None,
+                Some(format!("{}, the tags are listed without login", e)),
+            ),
+        }
+    }
+
+    /// exchanges the credentials for a token, which is reused for later requests
+    fn login(credentials: &Credentials, settings: &Settings) -> Result<String, Error> {
+        lazy_static::lazy_static! {
+            static ref TOKENS: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
+        }
+        if let Some(token) = TOKENS.lock().unwrap().get(&credentials.username) {
+            return Ok(token.clone());
+        }
+
+        let mut body = HashMap::new();
+        body.insert("username", &credentials.username);
+        body.insert("password", &credentials.password);
+        let request = super::client(settings)
+            .post("https://hub.docker.com/v2/users/login")
+            .json(&body);
+        let response = super::send(request, settings)?;
+        if !response.status().is_success() {
+            return Err(Error::Fetching(format!(
+                "login to docker hub failed for {}: {}",
+                credentials.username,
+                response.status()
+            )));
+        }
+        let body = super::read_body(response, settings)?;
+        let token = match serde_json::from_str::<Login>(&body) {
+            Ok(login) => login.token,
+            Err(e) => return Err(Error::Converting(format!("invalid json: {}", e))),
         };
 
+        TOKENS
+            .lock()
+            .unwrap()
+            .insert(credentials.username.clone(), token.clone());
+        Ok(token)
+    }
+
+    /// fetches tag information from a url
+    pub fn with_url(
+        url: &str,
+        auth: Option<String>,
+        settings: &Settings,
+    ) -> Result<super::Repo, Error> {
+        let mut request = super::client(settings).get(url);
+        if let Some(auth) = &auth {
+            request = request.header(reqwest::header::AUTHORIZATION, auth);
+        }
         //convert it to json
-        let tags = match response.json::<Self>() {
+        let body = super::fetch_text(request, repo_of_url(url), settings)?;
+        Self::parse(&body, url, auth, settings)
+    }
+
+    fn parse(
+        body: &str,
+        url: &str,
+        auth: Option<String>,
+        settings: &Settings,
+    ) -> Result<super::Repo, Error> {
+        let tags = match serde_json::from_str::<Self>(body) {
             Ok(result) => result,
             Err(e) => return Err(Error::Converting(format!("invalid json: {}", e))),
         };
@@ -68,9 +184,87 @@ impl DockerHub {
             return Err(Error::NoTagsFound);
         }
 
+        let mut converted: Vec<super::Tag> = tags.results.iter().map(|t| t.convert()).collect();
+        CAPABILITIES.sort(&mut converted, settings.tie_order);
+
         Ok(super::Repo {
-            tags: tags.results.iter().map(|t| t.convert()).collect(),
-            next_page: tags.next_page,
+            tags: converted,
+            next_page: tags.next_page.map(super::NextPage::Url),
+            auth,
+            settings: settings.clone(),
+            url: Some(url.to_string()),
+            warning: None,
         })
     }
 }
+
+/// the repository of a url of the tags, e.g. library/nginx
+fn repo_of_url(url: &str) -> &str {
+    let path = url
+        .trim_start_matches("https://hub.docker.com/v2/repositories/")
+        .split('?')
+        .next()
+        .unwrap_or(url);
+    path.strip_suffix("/tags").unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::repository::Settings;
+
+    #[test]
+    fn test_parse_with_unknown_fields() {
+        let body = r#"{
+            "count": 2,
+            "next": null,
+            "previous": null,
+            "some_new_field": {"nested": [1, 2]},
+            "results": [
+                {
+                    "name": "1.25",
+                    "last_updated": "2023-06-01T10:00:00.000000Z",
+                    "tag_status": "active",
+                    "digest": "sha256:list",
+                    "content_type": "image",
+                    "images": [
+                        {"architecture": "amd64", "os": "linux", "size": 1048576, "digest": "sha256:a", "new": true},
+                        {"architecture": "arm64", "os": "linux", "variant": "v8"}
+                    ]
+                },
+                {"name": "old", "last_updated": null}
+            ]
+        }"#;
+        let repo = super::DockerHub::parse(body, "", None, &Settings::default()).unwrap();
+
+        let tags = repo.get_tags();
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags[0].get_name(), "1.25");
+        assert_eq!(tags[0].get_details().len(), 2);
+        assert_eq!(tags[0].get_details()[0].size, Some(1048576));
+        assert_eq!(tags[0].get_details()[1].size, None);
+        assert_eq!(tags[0].get_digest(), Some("sha256:list"));
+        assert_eq!(tags[0].get_details()[0].digest.as_deref(), Some("sha256:a"));
+        assert_eq!(tags[0].pin_digest(), Some("sha256:list"));
+        assert_eq!(tags[1].get_name(), "old");
+        assert!(tags[1].last_updated().is_none());
+        assert_eq!(tags[1].pin_digest(), None);
+    }
+
+    #[test]
+    fn test_repo_of_url() {
+        let input: Vec<(&str, &str)> = vec![
+            (
+                "https://hub.docker.com/v2/repositories/library/nginx/tags?page_size=100",
+                "library/nginx",
+            ),
+            (
+                "https://hub.docker.com/v2/repositories/bitnami/redis/tags",
+                "bitnami/redis",
+            ),
+        ];
+
+        for i in input {
+            assert_eq!(super::repo_of_url(i.0), i.1);
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use serde::{Deserialize, Serialize};
+
+use crate::repository::{Capabilities, Error, Settings};
+
+const API: &str = "https://api.us-east-1.gallery.ecr.aws/describeImageTags";
+
+/// the gallery can't order tags, but sends up to 1000 per page
+const CAPABILITIES: Capabilities = Capabilities {
+    page_size: Some(("maxResults", 1000)),
+    newest_first: None,
+};
+
+#[derive(Serialize)]
+struct Request<'a> {
+    #[serde(rename(serialize = "registryAliasName"))]
+    alias: &'a str,
+    #[serde(rename(serialize = "repositoryName"))]
+    repository: &'a str,
+    #[serde(
+        rename(serialize = "nextToken"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    next_token: Option<&'a str>,
+    #[serde(
+        rename(serialize = "maxResults"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    max_results: Option<usize>,
+}
+
+#[derive(Deserialize, Default)]
+struct ImageDetail {
+    #[serde(rename(deserialize = "imageSizeInBytes"))]
+    size: Option<usize>,
+    #[serde(rename(deserialize = "imagePushedAt"))]
+    pushed_at: Option<serde_json::Value>,
+    #[serde(rename(deserialize = "imageDigest"))]
+    digest: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ImageTag {
+    #[serde(rename(deserialize = "imageTag"))]
+    tag_name: String,
+    #[serde(rename(deserialize = "createdAt"))]
+    created_at: Option<serde_json::Value>,
+    #[serde(rename(deserialize = "imageDetail"), default)]
+    detail: ImageDetail,
+}
+
+impl ImageTag {
+    fn convert(&self) -> super::Tag {
+        let time = self.detail.pushed_at.as_ref().or(self.created_at.as_ref());
+        super::Tag {
+            created: self.created_at.as_ref().and_then(to_rfc3339),
+            name: self.tag_name.clone(),
+            last_updated: time.and_then(to_rfc3339),
+            digest: self.detail.digest.clone(),
+            details: vec![super::TagDetails {
+                arch: None,
+                variant: None,
+                os: None,
+                size: self.detail.size,
+                digest: self.detail.digest.clone(),
+            }],
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct EcrPublic {
+    #[serde(rename(deserialize = "imageTagDetails"), default)]
+    tags: Vec<ImageTag>,
+    #[serde(rename(deserialize = "nextToken"))]
+    next_token: Option<String>,
+}
+
+impl EcrPublic {
+    /// fetches tag information of public.ecr.aws/{alias}/{repository}
+    pub fn create_repo(
+        alias: &str,
+        repository: &str,
+        next_token: Option<&str>,
+        settings: &Settings,
+    ) -> Result<super::Repo, Error> {
+        let body = Request {
+            alias,
+            repository,
+            next_token,
+            max_results: CAPABILITIES.page_size(settings).map(|(_, size)| size),
+        };
+        let request = super::client(settings).post(API).json(&body);
+        let text = super::fetch_text(
+            request,
+            &format!("public.ecr.aws/{}/{}", alias, repository),
+            settings,
+        )?;
+
+        Self::parse(&text, alias, repository, settings)
+    }
+
+    /// converts the json answer of the gallery api
+    fn parse(
+        text: &str,
+        alias: &str,
+        repository: &str,
+        settings: &Settings,
+    ) -> Result<super::Repo, Error> {
+        let tags: Self = match serde_json::from_str(text) {
+            Ok(result) => result,
+            Err(e) => return Err(Error::Converting(format!("invalid json: {}", e))),
+        };
+
+        if tags.tags.is_empty() {
+            return Err(Error::NoTagsFound);
+        }
+
+        let mut converted: Vec<super::Tag> = tags.tags.iter().map(|t| t.convert()).collect();
+        CAPABILITIES.sort(&mut converted, settings.tie_order);
+
+        Ok(super::Repo {
+            tags: converted,
+            next_page: tags.next_token.map(|token| super::NextPage::EcrPublic {
+                alias: alias.to_string(),
+                repository: repository.to_string(),
+                token,
+            }),
+            auth: None,
+            settings: settings.clone(),
+            // the repository is named in the body of the request
+            url: Some(API.to_string()),
+            warning: None,
+        })
+    }
+}
+
+/// the api sends either a date string or seconds since the epoch
+fn to_rfc3339(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(n) => {
+            let seconds = n.as_f64()?;
+            let time = chrono::NaiveDateTime::from_timestamp_opt(seconds as i64, 0)?;
+            Some(chrono::DateTime::<chrono::Utc>::from_utc(time, chrono::Utc).to_rfc3339())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::repository::{NextPage, Settings};
+
+    #[test]
+    fn test_parse() {
+        let text = r#"{
+            "imageTagDetails": [
+                {
+                    "imageTag": "1.25",
+                    "createdAt": "2023-06-01T10:00:00.000Z",
+                    "imageDetail": {
+                        "imageDigest": "sha256:abc",
+                        "imageSizeInBytes": 1048576,
+                        "imagePushedAt": "2023-06-02T10:00:00.000Z"
+                    }
+                },
+                { "imageTag": "latest", "createdAt": 1685613600 }
+            ],
+            "nextToken": "next"
+        }"#;
+        let repo = super::EcrPublic::parse(text, "nginx", "nginx", &Settings::default()).unwrap();
+
+        let tags = repo.get_tags();
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags[0].get_name(), "1.25");
+        assert_eq!(tags[0].get_details()[0].size, Some(1048576));
+        assert_eq!(tags[0].get_digest(), Some("sha256:abc"));
+        assert_eq!(tags[1].get_digest(), None);
+        assert_eq!(tags[0].created.as_deref(), Some("2023-06-01T10:00:00.000Z"));
+        assert_eq!(
+            tags[0].last_updated.as_deref(),
+            Some("2023-06-02T10:00:00.000Z")
+        );
+        assert_eq!(
+            tags[1].last_updated.as_deref(),
+            Some("2023-06-01T10:00:00+00:00")
+        );
+        assert!(matches!(
+            repo.next_page,
+            Some(NextPage::EcrPublic { token, .. }) if token == "next"
+        ));
+    }
+
+    #[test]
+    fn test_parse_empty() {
+        assert!(super::EcrPublic::parse(
+            r#"{"imageTagDetails": []}"#,
+            "a",
+            "b",
+            &Settings::default()
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,498 @@
+use std::collections::HashMap;
+
+use reqwest::header::{ACCEPT, AUTHORIZATION, LINK, WWW_AUTHENTICATE};
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+use crate::repository::credentials::Credentials;
+use crate::repository::{Capabilities, Error, Settings};
+
+/// the tag list of the registry api only knows names and is sorted by name
+const CAPABILITIES: Capabilities = Capabilities {
+    page_size: Some(("n", 1000)),
+    newest_first: None,
+};
+
+#[derive(Deserialize)]
+struct TagList {
+    #[serde(default)]
+    tags: Option<Vec<String>>,
+}
+
+/// manifests and indexes of docker and oci, an index lists a manifest per platform
+const MANIFEST_TYPES: &str = "application/vnd.docker.distribution.manifest.list.v2+json, application/vnd.oci.image.index.v1+json, application/vnd.docker.distribution.manifest.v2+json, application/vnd.oci.image.manifest.v1+json";
+
+#[derive(Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    manifests: Vec<ManifestEntry>,
+    #[serde(default)]
+    layers: Vec<serde_json::Value>,
+    /// the blob with the image config, only single manifests have one
+    config: Option<Descriptor>,
+}
+
+#[derive(Deserialize)]
+struct Descriptor {
+    digest: String,
+}
+
+/// the part of an image config with the build time
+#[derive(Deserialize)]
+struct ImageConfig {
+    created: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ManifestEntry {
+    digest: String,
+    platform: Option<Platform>,
+}
+
+#[derive(Deserialize)]
+struct Platform {
+    architecture: String,
+    os: String,
+    variant: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Token {
+    token: Option<String>,
+    access_token: Option<String>,
+}
+
+/// a registry with the docker registry http api v2, e.g. ghcr.io or quay.io
+pub struct RegistryV2;
+
+impl RegistryV2 {
+    /// fetches the tags of registry/repo
+    pub fn create_repo(
+        registry: &str,
+        repo: &str,
+        settings: &Settings,
+    ) -> Result<super::Repo, Error> {
+        let url = CAPABILITIES.url(
+            &format!("{}/v2/{}/tags/list", base_url(registry), repo),
+            settings,
+        );
+        Self::with_url(&url, None, settings)
+    }
+
+    /// fetches tags from a url, asks for a token when the registry wants one
+    pub fn with_url(
+        url: &str,
+        auth: Option<String>,
+        settings: &Settings,
+    ) -> Result<super::Repo, Error> {
+        let mut auth = auth;
+        let response = Self::send(url, &mut auth, None, settings)?;
+
+        let link = response
+            .headers()
+            .get(LINK)
+            .and_then(|h| h.to_str().ok())
+            .map(String::from);
+        let body = super::read_body(response, settings)?;
+        Self::parse(&body, link.as_deref(), url, auth, settings)
+    }
+
+    /// when the image of a tag was built, an index is followed to the manifest of its first platform
+    pub fn created(
+        registry: &str,
+        repo: &str,
+        tag: &str,
+        settings: &Settings,
+    ) -> Result<String, Error> {
+        let url = |path: &str| format!("{}/v2/{}/{}", base_url(registry), repo, path);
+        let mut auth = None;
+        let mut manifest =
+            Self::manifest(&url(&format!("manifests/{}", tag)), &mut auth, settings)?;
+        if let Some(entry) = manifest.manifests.iter().find(|e| {
+            // attestations are stored as manifests of an unknown platform
+            matches!(&e.platform, Some(platform) if platform.architecture != "unknown")
+        }) {
+            let digest = entry.digest.clone();
+            manifest = Self::manifest(&url(&format!("manifests/{}", digest)), &mut auth, settings)?;
+        }
+
+        let config = match manifest.config {
+            Some(config) => config,
+            None => return Err(Error::Converting("the manifest has no config".into())),
+        };
+        let response = Self::send(
+            &url(&format!("blobs/{}", config.digest)),
+            &mut auth,
+            None,
+            settings,
+        )?;
+        match serde_json::from_str::<ImageConfig>(&super::read_body(response, settings)?) {
+            Ok(ImageConfig {
+                created: Some(created),
+            }) => Ok(created),
+            Ok(_) => Err(Error::Converting(
+                "no build date in the image config".into(),
+            )),
+            Err(e) => Err(Error::Converting(format!("invalid image config: {}", e))),
+        }
+    }
+
+    /// fetches a manifest or an index
+    fn manifest(
+        url: &str,
+        auth: &mut Option<String>,
+        settings: &Settings,
+    ) -> Result<Manifest, Error> {
+        let response = Self::send(url, auth, Some(MANIFEST_TYPES), settings)?;
+        match serde_json::from_str(&super::read_body(response, settings)?) {
+            Ok(manifest) => Ok(manifest),
+            Err(e) => Err(Error::Converting(format!("invalid manifest: {}", e))),
+        }
+    }
+
+    /// counts the layers of each platform of a tag, which needs a request per platform
+    pub fn layers(
+        registry: &str,
+        repo: &str,
+        tag: &str,
+        settings: &Settings,
+    ) -> Result<Vec<super::Layers>, Error> {
+        let manifest_url =
+            |reference: &str| format!("{}/v2/{}/manifests/{}", base_url(registry), repo, reference);
+        let mut auth = None;
+        let manifest = Self::manifest(&manifest_url(tag), &mut auth, settings)?;
+
+        // a single manifest doesn't tell its platform
+        if manifest.manifests.is_empty() {
+            return Ok(vec![super::Layers {
+                os: None,
+                arch: None,
+                variant: None,
+                count: manifest.layers.len(),
+            }]);
+        }
+
+        let mut layers = vec![];
+        for entry in manifest.manifests {
+            let platform = match entry.platform {
+                // attestations are stored as manifests of an unknown platform
+                Some(platform) if platform.architecture != "unknown" => platform,
+                _ => continue,
+            };
+            let count = Self::manifest(&manifest_url(&entry.digest), &mut auth, settings)?
+                .layers
+                .len();
+            layers.push(super::Layers {
+                os: Some(platform.os),
+                arch: Some(platform.architecture),
+                variant: platform.variant,
+                count,
+            });
+        }
+        Ok(layers)
+    }
+
+    /// sends a request and asks for a token once, when the registry wants one
+    fn send(
+        url: &str,
+        auth: &mut Option<String>,
+        accept: Option<&str>,
+        settings: &Settings,
+    ) -> Result<reqwest::blocking::Response, Error> {
+        let mut response = Self::get(url, auth, accept, settings)?;
+        if response.status() == StatusCode::UNAUTHORIZED {
+            let challenge = response
+                .headers()
+                .get(WWW_AUTHENTICATE)
+                .and_then(|h| h.to_str().ok())
+                .unwrap_or_default()
+                .to_string();
+            *auth = Some(match challenge.split(' ').next() {
+                Some("Basic") => match Credentials::find(host_of(url), settings) {
+                    Some(credentials) => credentials.basic_auth(),
+                    None => {
+                        return Err(Error::Fetching(format!(
+                        "{} needs a login, use docker login or --username and --registry-host with REEL_MOBY_PASSWORD",
+                        host_of(url)
+                    )))
+                    }
+                },
+                _ => Self::token(url, &challenge, settings)?,
+            });
+            response = Self::get(url, auth, accept, settings)?;
+            if response.status() == StatusCode::UNAUTHORIZED {
+                return Err(Error::Fetching(format!(
+                    "authentication failed for {}, check your credentials",
+                    host_of(url)
+                )));
+            }
+        }
+        if !response.status().is_success() {
+            return Err(Error::Fetching(format!(
+                "registry answered {}",
+                response.status()
+            )));
+        }
+        Ok(response)
+    }
+
+    fn get(
+        url: &str,
+        auth: &Option<String>,
+        accept: Option<&str>,
+        settings: &Settings,
+    ) -> Result<reqwest::blocking::Response, Error> {
+        let mut request = super::client(settings).get(url);
+        if let Some(auth) = auth {
+            request = request.header(AUTHORIZATION, auth);
+        }
+        if let Some(accept) = accept {
+            request = request.header(ACCEPT, accept);
+        }
+        super::send(request, settings)
+    }
+
+    /// gets a bearer token as described in the challenge, with the credentials of `docker login` if there are some
+    fn token(url: &str, challenge: &str, settings: &Settings) -> Result<String, Error> {
+        let params = match challenge.strip_prefix("Bearer ") {
+            Some(params) => parse_challenge(params),
+            None => {
+                return Err(Error::Fetching(format!(
+                    "unsupported authentication: {}",
+                    challenge
+                )))
+            }
+        };
+        let realm = match params.get("realm") {
+            Some(realm) => realm,
+            None => return Err(Error::Fetching("no realm to get a token from".into())),
+        };
+
+        let mut request = super::client(settings).get(realm.as_str()).query(
+            &params
+                .iter()
+                .filter(|(key, _)| key.as_str() != "realm")
+                .collect::<Vec<_>>(),
+        );
+        let host = host_of(url);
+        if let Some(credentials) = Credentials::find(host, settings) {
+            request = request.basic_auth(credentials.username, Some(credentials.password));
+        }
+
+        let response = super::send(request, settings)?;
+        if !response.status().is_success() {
+            return Err(Error::Fetching(format!(
+                "{} refused a token: {}",
+                host,
+                response.status()
+            )));
+        }
+        let body = super::read_body(response, settings)?;
+        match serde_json::from_str::<Token>(&body) {
+            Ok(Token {
+                token: Some(token), ..
+            })
+            | Ok(Token {
+                access_token: Some(token),
+                ..
+            }) => Ok(format!("Bearer {}", token)),
+            Ok(_) => Err(Error::Converting("no token in answer".into())),
+            Err(e) => Err(Error::Converting(format!("invalid json: {}", e))),
+        }
+    }
+
+    /// converts the tag list, the next page is given by the link header
+    fn parse(
+        body: &str,
+        link: Option<&str>,
+        url: &str,
+        auth: Option<String>,
+        settings: &Settings,
+    ) -> Result<super::Repo, Error> {
+        let list: TagList = match serde_json::from_str(body) {
+            Ok(list) => list,
+            Err(e) => return Err(Error::Converting(format!("invalid json: {}", e))),
+        };
+        let tags = list.tags.unwrap_or_default();
+        if tags.is_empty() {
+            return Err(Error::NoTagsFound);
+        }
+
+        Ok(super::Repo {
+            tags: tags
+                .into_iter()
+                .map(|name| super::Tag {
+                    name,
+                    details: vec![],
+                    last_updated: None,
+                    digest: None,
+                    created: None,
+                })
+                .collect(),
+            next_page: link
+                .and_then(|l| next_link(l, url))
+                .map(super::NextPage::Link),
+            auth,
+            settings: settings.clone(),
+            url: Some(url.to_string()),
+            warning: None,
+        })
+    }
+}
+
+fn host_of(url: &str) -> &str {
+    url.trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .split('/')
+        .next()
+        .unwrap_or_default()
+}
+
+/// local registries usually don't have a certificate
+fn base_url(registry: &str) -> String {
+    match registry.starts_with("localhost") || registry.starts_with("127.0.0.1") {
+        true => format!("http://{}", registry),
+        false => format!("https://{}", registry),
+    }
+}
+
+/// the url with rel="next" of a link header like `</v2/a/tags/list?last=b&n=2>; rel="next"`, relative to the url of the request
+fn next_link(header: &str, url: &str) -> Option<String> {
+    let next = header.split(',').find(|l| {
+        l.split(';')
+            .skip(1)
+            .any(|p| matches!(p.trim(), "rel=\"next\"" | "rel=next"))
+    })?;
+    let target = next.split(';').next()?.trim();
+    let target = target.strip_prefix('<')?.strip_suffix('>')?;
+
+    if target.starts_with("http://") || target.starts_with("https://") {
+        return Some(target.to_string());
+    }
+    let scheme_end = url.find("://")? + 3;
+    let host_end = url[scheme_end..]
+        .find('/')
+        .map(|i| i + scheme_end)
+        .unwrap_or(url.len());
+    Some(format!("{}{}", &url[..host_end], target))
+}
+
+/// the parameters of a challenge like `realm="https://ghcr.io/token",service="ghcr.io"`
+fn parse_challenge(params: &str) -> HashMap<String, String> {
+    lazy_static::lazy_static! {
+        static ref REGEX: regex::Regex = regex::Regex::new(r#"(\w+)="([^"]*)""#).unwrap();
+    }
+    REGEX
+        .captures_iter(params)
+        .map(|caps| (caps[1].to_string(), caps[2].to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::repository::{NextPage, Settings};
+
+    #[test]
+    fn test_next_link() {
+        let url = "https://ghcr.io/v2/owner/image/tags/list?n=2";
+        let input: Vec<(&str, Option<&str>)> = vec![
+            (
+                "</v2/owner/image/tags/list?last=b&n=2>; rel=\"next\"",
+                Some("https://ghcr.io/v2/owner/image/tags/list?last=b&n=2"),
+            ),
+            (
+                "<https://other/v2/x/tags/list?last=b>; rel=\"next\"",
+                Some("https://other/v2/x/tags/list?last=b"),
+            ),
+            ("</v2/owner/image/tags/list?n=2>; rel=\"prev\"", None),
+            ("", None),
+        ];
+
+        for i in input {
+            assert_eq!(super::next_link(i.0, url).as_deref(), i.1, "{}", i.0);
+        }
+    }
+
+    #[test]
+    fn test_parse_with_link_header() {
+        let repo = super::RegistryV2::parse(
+            r#"{"name": "owner/image", "tags": ["1.0", "1.1"]}"#,
+            Some("</v2/owner/image/tags/list?last=1.1&n=2>; rel=\"next\""),
+            "https://ghcr.io/v2/owner/image/tags/list?n=2",
+            Some("Bearer token".into()),
+            &Settings::default(),
+        )
+        .unwrap();
+
+        assert_eq!(repo.get_tags().len(), 2);
+        assert_eq!(repo.get_tags()[1].get_name(), "1.1");
+        assert!(matches!(
+            repo.next_page,
+            Some(NextPage::Link(url)) if url == "https://ghcr.io/v2/owner/image/tags/list?last=1.1&n=2"
+        ));
+
+        let last = super::RegistryV2::parse(
+            r#"{"name": "owner/image", "tags": ["1.2"]}"#,
+            None,
+            "https://ghcr.io/v2/owner/image/tags/list?last=1.1&n=2",
+            None,
+            &Settings::default(),
+        )
+        .unwrap();
+        assert!(last.next_page.is_none());
+    }
+
+    #[test]
+    fn test_parse_manifest() {
+        let index: super::Manifest = serde_json::from_str(
+            r#"{
+                "schemaVersion": 2,
+                "mediaType": "application/vnd.oci.image.index.v1+json",
+                "manifests": [
+                    {"digest": "sha256:a", "platform": {"architecture": "amd64", "os": "linux"}},
+                    {"digest": "sha256:b", "platform": {"architecture": "arm", "os": "linux", "variant": "v7"}},
+                    {"digest": "sha256:c", "platform": {"architecture": "unknown", "os": "unknown"}}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(index.manifests.len(), 3);
+        assert!(index.layers.is_empty());
+        assert_eq!(
+            index.manifests[1]
+                .platform
+                .as_ref()
+                .unwrap()
+                .variant
+                .as_deref(),
+            Some("v7")
+        );
+
+        let manifest: super::Manifest = serde_json::from_str(
+            r#"{
+                "schemaVersion": 2,
+                "config": {"digest": "sha256:config"},
+                "layers": [{"digest": "sha256:1"}, {"digest": "sha256:2"}]
+            }"#,
+        )
+        .unwrap();
+        assert!(manifest.manifests.is_empty());
+        assert_eq!(manifest.layers.len(), 2);
+        assert_eq!(manifest.config.unwrap().digest, "sha256:config");
+
+        let config: super::ImageConfig = serde_json::from_str(
+            r#"{"architecture": "amd64", "created": "2024-03-01T12:00:00.5Z", "config": {}}"#,
+        )
+        .unwrap();
+        assert_eq!(config.created.as_deref(), Some("2024-03-01T12:00:00.5Z"));
+    }
+
+    #[test]
+    fn test_parse_challenge() {
+        let params = super::parse_challenge(
+            r#"realm="https://ghcr.io/token",service="ghcr.io",scope="repository:owner/image:pull""#,
+        );
+        assert_eq!(params["realm"], "https://ghcr.io/token");
+        assert_eq!(params["scope"], "repository:owner/image:pull");
+    }
+}
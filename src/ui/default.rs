@@ -1,13 +1,19 @@
-use std::{io, thread};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use crate::Opt;
 use termion::event::Key;
-use termion::raw::IntoRawMode;
-use tui::backend::TermionBackend;
-use tui::layout::{Constraint, Direction, Layout};
-use tui::Terminal;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::widgets::Clear;
 
+use crate::common::external;
 use crate::repository;
+use crate::widget::common_tags;
+use crate::widget::confirm;
+use crate::widget::file_picker;
 use crate::widget::info;
 use crate::widget::repo_entry;
 use crate::widget::service_switcher;
@@ -20,6 +26,38 @@ pub struct Ui {
     services: crate::widget::service_switcher::ServiceSwitcher,
     details: crate::widget::details::Details,
     info: crate::widget::info::Info,
+    action: Option<external::Pending>,
+    filter: tag_list::Filter,
+    compact: bool,
+    settings: repository::Settings,
+    view: crate::widget::details::View,
+    layers: super::lookup::Lookup<Vec<crate::repository::Layers>>,
+    build_dates: super::lookup::Lookup<chrono::DateTime<chrono::Utc>>,
+    list_view: tag_list::ListView,
+    cadence: Option<crate::widget::cadence::Cadence>,
+    /// the keys, shown until any key is pressed
+    help: Option<crate::widget::help::Help>,
+    /// the last selected tag of each repo
+    selections: HashMap<String, String>,
+    digests: super::digests::DigestCache,
+    /// the tags of the chosen repo, while they are fetched
+    fetch: super::fetch::TagFetch,
+    /// lines and the image to write to them, waiting for a confirmation, and how many pinned lines are skipped
+    confirm: Option<(Vec<usize>, String, usize)>,
+    /// the summary of changes shown before saving
+    save_dialog: Option<confirm::Confirm>,
+    /// the path to save a copy to, and whether an existing file is confirmed to be replaced
+    save_as: Option<(repo_entry::RepoEntry, bool)>,
+    /// Ctrl+q was pressed once with unsaved changes
+    quit_pending: bool,
+    /// the loaded files to jump to
+    file_picker: Option<file_picker::FilePicker>,
+    /// the repos whose common tags are fetched
+    pending_common: Option<(Vec<String>, super::CommonTagsFetch)>,
+    /// the tags all related repos have
+    common_tags: Option<common_tags::CommonTags>,
+    /// when the tag in the file was updated, to show only newer tags
+    pending_newer: Option<super::TagTimeFetch>,
 }
 
 #[derive(PartialEq, Clone)]
@@ -53,6 +91,27 @@ impl std::iter::Iterator for State {
 }
 
 impl Ui {
+    /// whether a popup takes the typed keys
+    fn in_dialog(&self) -> bool {
+        self.common_tags.is_some()
+            || self.file_picker.is_some()
+            || self.save_as.is_some()
+            || self.save_dialog.is_some()
+            || self.confirm.is_some()
+    }
+
+    /// look up the age of all tags in the file which are not pinned, when a threshold is configured
+    fn check_stale(&self, opt: &Opt) -> Option<mpsc::Receiver<(usize, chrono::Duration)>> {
+        let days = opt.config.stale_days?;
+        let lines = self
+            .services
+            .get_image_lines()
+            .into_iter()
+            .filter(|(i, _)| !self.services.is_pinned(*i))
+            .collect();
+        Some(super::spawn_stale_check(lines, days, self.settings.clone()))
+    }
+
     pub fn run(opt: &Opt) {
         let repo_id = opt.repo.as_deref();
 
@@ -60,25 +119,83 @@ impl Ui {
             state: State::SelectService,
             repo: repo_entry::RepoEntry::new(repo_id),
             tags: tag_list::TagList::with_status("Tags are empty"),
-            services: service_switcher::ServiceSwitcher::new(&opt.file).unwrap(),
+            services: service_switcher::ServiceSwitcher::new(&opt.file, opt.with_override).unwrap(),
             details: crate::widget::details::Details::new(),
             info: info::Info::new("Select image of edit Repository"),
+            action: None,
+            filter: tag_list::Filter::new(opt.since)
+                .with_variants(&opt.config.variants)
+                .with_platform(opt.arch),
+            compact: opt.compact,
+            settings: opt.settings(),
+            view: crate::widget::details::View::new().with_platform(opt.platform),
+            layers: super::lookup::Lookup::layer_counts(),
+            build_dates: super::lookup::Lookup::build_dates(),
+            list_view: tag_list::ListView {
+                accessible: opt.accessible,
+                ..tag_list::ListView::default()
+            },
+            cadence: None,
+            help: None,
+            selections: HashMap::new(),
+            digests: super::digests::DigestCache::load(),
+            fetch: super::fetch::TagFetch::new(super::tag_cache(opt)),
+            confirm: None,
+            save_dialog: None,
+            save_as: None,
+            quit_pending: false,
+            file_picker: None,
+            pending_common: None,
+            pending_newer: None,
+            common_tags: None,
         };
 
-        if opt.repo.is_none() {
-            ui.tags = tag_list::TagList::with_repo_name(ui.repo.get());
+        if opt.file.is_empty() {
+            ui.info.set_text(&format!(
+                "Opened {}, found in the current folder",
+                ui.services.current_file().display()
+            ));
+        }
+
+        //show which images are deployed, works only with a reachable docker daemon
+        let mut running_receiver = super::spawn_container_check();
+        ui.services.set_pinned(opt.config.pinned_repos.clone());
+
+        let mut stale_receiver = ui.check_stale(opt);
+
+        if opt.read_only {
+            ui.info.set_mode("read-only mode");
+        }
+
+        // load tags if a repository was given thorugh paramter
+        if let Some(session) = &opt.session {
+            ui.repo.set(session.repo.clone());
+            ui.tags = super::import_session(session, &ui.filter);
+            ui.details = ui.tags.create_detail_widget(ui.view);
+            ui.info.set_text("Imported session");
+        } else if opt.repo.is_some() {
+            ui.tags = ui.fetch.start(ui.repo.get(), &ui.settings);
         }
 
         //setup tui
-        let stdout = io::stdout().into_raw_mode().unwrap();
-        let backend = TermionBackend::new(stdout);
-        let mut terminal = Terminal::new(backend).unwrap();
+        let mut terminal = super::setup_terminal();
 
         //setup input thread
-        let receiver = super::spawn_stdin_channel();
+        let mut receiver = super::spawn_stdin_channel();
+
+        // imported sessions are not refreshed
+        let refresh_idle = opt
+            .refresh_idle
+            .filter(|_| opt.session.is_none())
+            .map(Duration::from_secs);
+        let mut last_input = Instant::now();
+        let mut last_refresh = Instant::now();
 
         //core interaction loop
         'core: loop {
+            //spin while the tags are fetched
+            ui.tags.set_spinner(ui.fetch.spinner());
+
             //draw
             terminal
                 .draw(|rect| {
@@ -98,39 +215,675 @@ impl Ui {
                     let (list, state) = ui.services.render(ui.state == State::SelectService);
                     rect.render_stateful_widget(list, chunks[0], state);
                     rect.render_widget(ui.repo.render(ui.state == State::EditRepo), chunks[1]);
-                    let (list, state) = ui.tags.render(ui.state == State::SelectTag);
-                    let more_chunks = Layout::default()
-                        .direction(Direction::Horizontal)
-                        .constraints([Constraint::Min(15), Constraint::Length(28)].as_ref())
-                        .split(chunks[2]);
-                    rect.render_stateful_widget(list, more_chunks[0], state);
-                    rect.render_widget(ui.details.render(), more_chunks[1]);
+                    let (list, state) = ui.tags.render(ui.state == State::SelectTag, ui.list_view);
+                    if ui.compact {
+                        let more_chunks = Layout::default()
+                            .direction(Direction::Vertical)
+                            .constraints([Constraint::Min(6), Constraint::Length(1)].as_ref())
+                            .split(chunks[2]);
+                        rect.render_stateful_widget(list, more_chunks[0], state);
+                        rect.render_widget(ui.details.render_summary(), more_chunks[1]);
+                    } else {
+                        let more_chunks = Layout::default()
+                            .direction(Direction::Horizontal)
+                            .constraints([Constraint::Min(15), Constraint::Length(28)].as_ref())
+                            .split(chunks[2]);
+                        rect.render_stateful_widget(list, more_chunks[0], state);
+                        rect.render_widget(ui.details.render(), more_chunks[1]);
+                    }
                     rect.render_widget(ui.info.render(), chunks[3]);
+
+                    if ui.info.is_expanded() {
+                        let area = super::centered_rect(80, 60, rect.size());
+                        rect.render_widget(Clear, area);
+                        rect.render_widget(ui.info.render_expanded(), area);
+                    }
+                    if let Some(cadence) = &ui.cadence {
+                        let area = super::centered_rect(80, 60, rect.size());
+                        let data = cadence.data();
+                        rect.render_widget(Clear, area);
+                        rect.render_widget(cadence.render(&data), area);
+                    }
+                    if let Some(dialog) = &ui.save_dialog {
+                        let area = super::centered_rect(80, 60, rect.size());
+                        rect.render_widget(Clear, area);
+                        rect.render_widget(dialog.render(), area);
+                    }
+                    if let Some((entry, _)) = &ui.save_as {
+                        let area = super::centered_rect(80, 60, rect.size());
+                        let area = Rect { height: 3, ..area };
+                        rect.render_widget(Clear, area);
+                        rect.render_widget(entry.render(true), area);
+                    }
+                    if let Some(picker) = &mut ui.file_picker {
+                        let area = super::centered_rect(80, 60, rect.size());
+                        let (list, state) = picker.render();
+                        rect.render_widget(Clear, area);
+                        rect.render_stateful_widget(list, area, state);
+                    }
+                    if let Some(common) = &mut ui.common_tags {
+                        let area = super::centered_rect(80, 60, rect.size());
+                        let (list, state) = common.render();
+                        rect.render_widget(Clear, area);
+                        rect.render_stateful_widget(list, area, state);
+                    }
+                    if let Some(help) = &ui.help {
+                        let area = super::centered_rect(80, 80, rect.size());
+                        rect.render_widget(Clear, area);
+                        rect.render_widget(help.render(), area);
+                    }
                 })
                 .unwrap();
 
+            //mark stale images
+            if let Some(receiver) = &stale_receiver {
+                while let Ok((line, age)) = receiver.try_recv() {
+                    ui.services.set_stale(line, age);
+                }
+            }
+
+            if let Ok(containers) = running_receiver.try_recv() {
+                ui.services.set_running(containers);
+            }
+
+            //report finished background actions
+            if let Some(message) = ui.action.as_ref().and_then(|a| a.poll()) {
+                ui.info.set_text(&message);
+                ui.action = None;
+            }
+
+            //add the next page of tags once it arrived
+            if ui.tags.poll_next_page() {
+                ui.details = ui.tags.create_detail_widget(ui.view);
+            }
+
+            //show the tags once they are fetched
+            if let Some((tags, message)) = ui.fetch.poll(&ui.filter, &ui.tags) {
+                if let Some(tags) = tags {
+                    ui.tags = tags;
+                    ui.details = ui.tags.create_detail_widget(ui.view);
+                }
+                if let Some(message) = message {
+                    ui.info.set_text(&message);
+                }
+            }
+
+            //select the tag again which was selected the last time the repo was shown
+            if super::remember_selection(&mut ui.selections, &mut ui.tags) {
+                ui.details = ui.tags.create_detail_widget(ui.view);
+            }
+
+            //mark tags whose digest changed since the last visit
+            super::check_moved(&mut ui.digests, &mut ui.tags);
+
+            //show the common tags of the related repos when they are fetched
+            if let Some((repos, receiver)) = &ui.pending_common {
+                if let Ok(common) = receiver.try_recv() {
+                    match common {
+                        Ok(tags) => {
+                            ui.info.set_text(&format!(
+                                "{} tags are shared by all of them",
+                                tags.len()
+                            ));
+                            ui.common_tags =
+                                Some(common_tags::CommonTags::new(repos.clone(), tags));
+                        }
+                        Err(e) => ui
+                            .info
+                            .set_text(&format!("Finding common tags failed: {}", e)),
+                    }
+                    ui.pending_common = None;
+                }
+            }
+
+            //only show the tags newer than the one in the file, once its date is known
+            if let Some(receiver) = &ui.pending_newer {
+                if let Ok(newer_than) = receiver.try_recv() {
+                    match newer_than {
+                        Ok(newer_than) => {
+                            let description = ui.filter.set_newer_than(Some(newer_than));
+                            ui.tags.set_filter(&ui.filter);
+                            ui.details = ui.tags.create_detail_widget(ui.view);
+                            ui.info.set_text(&description);
+                        }
+                        Err(e) => ui
+                            .info
+                            .set_text(&format!("Can't filter by the current tag: {}", e)),
+                    }
+                    ui.pending_newer = None;
+                }
+            }
+
+            //show the layer counts of the selected tag
+            if let Some(message) = ui.layers.poll() {
+                ui.info.set_text(&message);
+            }
+            if let Some(tag) = ui.tags.get_selected_name() {
+                if let Some(layers) = ui.layers.get(&ui.repo.get(), &tag, &ui.settings) {
+                    ui.details.set_layers(layers);
+                }
+            }
+
+            //show when the selected image was built
+            if let Some(message) = ui.build_dates.poll() {
+                ui.info.set_text(&message);
+            }
+            if let Some(tag) = ui.tags.get_selected_name() {
+                if let Some(built) = ui.build_dates.get(&ui.repo.get(), &tag, &ui.settings) {
+                    ui.details.set_built(built);
+                }
+            }
+
+            //refresh the tags when the user was idle for a while
+            if let Some(idle) = refresh_idle {
+                if last_input.elapsed() >= idle
+                    && last_refresh.elapsed() >= idle
+                    && ui.tags.is_loaded()
+                {
+                    // the shown tags stay until the new ones arrived
+                    if let Some(repo) = ui.tags.get_repo() {
+                        ui.fetch.reload(repo.to_string(), &ui.settings);
+                    }
+                    last_refresh = Instant::now();
+                }
+            }
+
             //handle input
-            match receiver.try_recv() {
-                Ok(Key::Ctrl('q')) => break 'core, //quit program without saving
+            let mut input = receiver.try_recv();
+            // with vim keys the lists move with j, k, g and G, which are typed text elsewhere,
+            // like in the filter of the tags once it was started
+            let typing = match ui.state {
+                State::EditRepo => true,
+                State::SelectTag => ui.tags.is_searching(),
+                State::SelectService => false,
+            };
+            if opt.config.vim_keys && !typing && !ui.in_dialog() {
+                input = input.map(super::vim_motion);
+            }
+            // only a second Ctrl+q in a row quits with unsaved changes
+            let quit_confirmed = ui.quit_pending;
+            if input.is_ok() {
+                last_input = Instant::now();
+                ui.quit_pending = false;
+            }
+            match input {
+                Ok(key) if ui.info.is_expanded() => ui.info.handle_input(key),
+                Ok(_) if ui.cadence.is_some() => ui.cadence = None,
+                Ok(_) if ui.help.is_some() => ui.help = None,
+                Ok(Key::Esc | Key::Ctrl('q')) if ui.common_tags.is_some() => {
+                    ui.common_tags = None;
+                }
+                Ok(key) if ui.common_tags.is_some() => {
+                    if let Some(tag) = ui.common_tags.as_mut().unwrap().handle_input(key) {
+                        ui.common_tags = None;
+                        ui.tags.select_tag(&tag);
+                        ui.details = ui.tags.create_detail_widget(ui.view);
+                        match ui.tags.get_selected_name() {
+                            Some(selected) if selected == tag => {
+                                ui.state = State::SelectTag;
+                                ui.info.set_text(&format!("Selected {}", tag));
+                            }
+                            _ => ui
+                                .info
+                                .set_text(&format!("{} is not listed, load more tags", tag)),
+                        }
+                    }
+                }
+                Ok(Key::Esc | Key::Ctrl('q')) if ui.file_picker.is_some() => {
+                    ui.file_picker = None;
+                }
+                Ok(key) if ui.file_picker.is_some() => {
+                    let file = match ui.file_picker.as_mut().unwrap().handle_input(key) {
+                        None => continue,
+                        Some(file) => file,
+                    };
+                    ui.file_picker = None;
+                    ui.state = State::SelectService;
+                    if !ui.services.select_file(file) {
+                        ui.info.set_text("The file has no images");
+                        continue;
+                    }
+                    match ui
+                        .services
+                        .extract_repo()
+                        .map(|s| repository::check_repo(&s))
+                    {
+                        Err(e) => ui.info.set_info(&format!("{}", e)),
+                        Ok(Err(e)) => ui.info.set_info(&format!("{}", e)),
+                        Ok(Ok(repo)) => {
+                            ui.repo.set(repo);
+                            ui.tags = ui.fetch.start(ui.repo.get(), &ui.settings);
+                            ui.details = ui.tags.create_detail_widget(ui.view);
+                        }
+                    }
+                }
+                Ok(Key::Esc) if ui.save_as.is_some() => {
+                    ui.save_as = None;
+                    ui.info.set_text("Saving cancelled");
+                }
+                Ok(Key::Char('\n')) if ui.save_as.is_some() => {
+                    let (entry, replace) = ui.save_as.as_mut().unwrap();
+                    let path = PathBuf::from(entry.get());
+                    if path.exists() && !*replace {
+                        *replace = true;
+                        ui.info.set_text(&format!(
+                            "{} exists, Return to replace it, Esc to cancel",
+                            path.display()
+                        ));
+                        continue;
+                    }
+                    ui.save_as = None;
+                    match ui.services.save_as(&path) {
+                        Err(e) => ui.info.set_info(&format!("Saving failed: {}", e)),
+                        Ok(()) => ui.info.set_text(&format!("Saved to {}", path.display())),
+                    }
+                }
+                Ok(key) if ui.save_as.is_some() => {
+                    let (entry, replace) = ui.save_as.as_mut().unwrap();
+                    entry.handle_input(key);
+                    *replace = false;
+                }
+                Ok(key) if ui.save_dialog.is_some() => {
+                    match ui.save_dialog.as_mut().unwrap().handle_input(key) {
+                        None => (),
+                        Some(false) => {
+                            ui.save_dialog = None;
+                            ui.info.set_text("Saving cancelled");
+                        }
+                        Some(true) => {
+                            ui.save_dialog = None;
+                            match ui.services.save(opt.backup || opt.config.backup) {
+                                Err(e) => ui.info.set_info(&format!("Saving failed: {}", e)),
+                                Ok(backups) if backups.is_empty() => {
+                                    ui.info.set_text("Saved compose file")
+                                }
+                                Ok(backups) => {
+                                    let backups: Vec<String> =
+                                        backups.iter().map(|b| b.display().to_string()).collect();
+                                    ui.info.set_text(&format!(
+                                        "Saved compose file (backup written to {})",
+                                        backups.join(" and ")
+                                    ))
+                                }
+                            }
+                        }
+                    }
+                }
+                Ok(key) if ui.confirm.is_some() => {
+                    let (lines, repo, skipped) = ui.confirm.take().unwrap();
+                    match key {
+                        Key::Char('\n') => {
+                            let changed = ui.services.change_lines(&lines, &repo);
+                            ui.info.set_text(&format!(
+                                "Changed {} lines to {}{}",
+                                changed,
+                                repo,
+                                skipped_pinned(skipped)
+                            ));
+                        }
+                        _ => ui.info.set_text("Update cancelled"),
+                    }
+                }
+                Ok(Key::Ctrl('b')) => {
+                    ui.cadence = Some(crate::widget::cadence::Cadence::new(&ui.tags.get_tags()));
+                }
+                Ok(Key::Char('?')) => ui.help = Some(crate::widget::help::Help::new(true)),
+                Ok(Key::Ctrl('v')) => ui.info.expand(),
+                Ok(Key::Alt('h')) => ui.info.expand_history(),
+                Ok(Key::Alt('y')) => {
+                    let names = ui.tags.get_visible_names();
+                    match names.len() {
+                        0 => ui.info.set_text("No tags to copy"),
+                        count => {
+                            ui.info
+                                .set_text(&format!("Copying {} tag names to clipboard", count));
+                            ui.action =
+                                Some(external::Action::CopyToClipboard(names.join("\n")).spawn());
+                        }
+                    }
+                }
+                Ok(Key::Alt('c')) => {
+                    let message = ui.tags.toggle_case_sensitive();
+                    ui.details = ui.tags.create_detail_widget(ui.view);
+                    ui.info.set_text(message);
+                }
+                Ok(Key::Ctrl('w')) => match ui.tags.get_url() {
+                    None => ui.info.set_text("No tags were requested yet"),
+                    Some(url) => {
+                        ui.info
+                            .set_text(&format!("Tags were requested from {}, copying it", url));
+                        ui.action =
+                            Some(external::Action::CopyToClipboard(url.to_string()).spawn());
+                    }
+                },
+                Ok(Key::Ctrl('f')) => {
+                    let files = ui.services.files();
+                    match files.len() {
+                        1 => ui.info.set_text("Only one file is loaded"),
+                        _ => ui.file_picker = Some(file_picker::FilePicker::new(files)),
+                    }
+                }
+                Ok(Key::Alt('e')) if opt.read_only => {
+                    ui.info.set_text("read-only mode: the file is not changed")
+                }
+                Ok(Key::Alt('e')) if ui.services.has_changes() => ui
+                    .info
+                    .set_text("Save or revert the changes before editing the file"),
+                Ok(Key::Alt('e')) => {
+                    let service = ui.services.current_service();
+                    let path = ui.services.current_file().to_path_buf();
+                    let (restored, result) = super::edit_file(terminal, &path);
+                    terminal = restored;
+                    if let Err(e) = result {
+                        ui.info.set_text(&format!("Editing failed: {}", e));
+                        continue;
+                    }
+                    match service_switcher::ServiceSwitcher::new(&opt.file, opt.with_override) {
+                        None => ui.info.set_text("Could not load the file after editing"),
+                        Some(services) => {
+                            ui.services = services;
+                            running_receiver = super::spawn_container_check();
+                            ui.services.set_pinned(opt.config.pinned_repos.clone());
+                            // the lines of the old contents are marked stale by the running check
+                            stale_receiver = ui.check_stale(opt);
+                            let found = match &service {
+                                Some(service) => ui.services.select_service(service),
+                                None => false,
+                            };
+                            if !found {
+                                ui.services.find_next_match();
+                            }
+                            ui.state = State::SelectService;
+                            ui.info.set_text(&format!("Reloaded {}", path.display()));
+                        }
+                    }
+                }
+                Ok(Key::Alt('i')) => {
+                    // the marked lines win over the ones named like the selected one
+                    let marked = ui.services.marked_repos();
+                    let from_marks = !marked.is_empty();
+                    let repos = match from_marks {
+                        true => marked,
+                        false => ui.services.related_repos(),
+                    };
+                    match repos.len() {
+                        0 => ui.info.set_text("Select an image line first"),
+                        1 if !from_marks => ui
+                            .info
+                            .set_text(&format!("No other repository is named like {}", repos[0])),
+                        1 => ui.info.set_text(&format!(
+                            "Mark another repository with space to compare it with {}",
+                            repos[0]
+                        )),
+                        _ => {
+                            ui.info.set_text(&format!(
+                                "Looking for tags all of {} have",
+                                repos.join(", ")
+                            ));
+                            let receiver =
+                                super::spawn_common_tags(repos.clone(), ui.settings.clone());
+                            ui.pending_common = Some((repos, receiver));
+                        }
+                    }
+                }
+                Ok(Key::Ctrl('q')) if quit_confirmed || !ui.services.has_changes() => break 'core, //quit program without saving
+                Ok(Key::Ctrl('q')) => {
+                    ui.quit_pending = true;
+                    ui.info
+                        .set_text("Unsaved changes — press Ctrl+q again to quit or Ctrl+s to save");
+                }
                 Ok(Key::Char('\t')) => {
                     ui.state.next();
                     ui.info.set_info(&ui.state);
                 }
-                Ok(Key::Ctrl('s')) => match ui.services.save() {
-                    Err(e) => {
-                        ui.info.set_info(&format!("{}", e));
-                        continue;
+                Ok(Key::Ctrl('s')) if opt.read_only => {
+                    ui.info.set_text("read-only mode: saving is disabled")
+                }
+                Ok(Key::Ctrl('s')) => {
+                    let changes = ui.services.get_changes();
+                    match changes.is_empty() {
+                        true => ui.info.set_text("Nothing to save"),
+                        false => {
+                            let title = format!("Save {} changes", changes.len());
+                            ui.save_dialog = Some(confirm::Confirm::new(&title, changes));
+                        }
+                    }
+                }
+                Ok(Key::Alt('s')) if opt.read_only => {
+                    ui.info.set_text("read-only mode: saving is disabled")
+                }
+                Ok(Key::Alt('s')) => {
+                    let path = ui.services.current_file().display().to_string();
+                    ui.save_as = Some((repo_entry::RepoEntry::prompt("Save as", &path), false));
+                    ui.info
+                        .set_text("Enter the path to save to, the opened file stays unchanged");
+                }
+                Ok(Key::Ctrl('y')) => {
+                    let mut text = ui.repo.get();
+                    let marked = ui.tags.get_marked();
+                    if !marked.is_empty() {
+                        text = marked
+                            .iter()
+                            .map(|tag| format!("{}:{}", ui.repo.get(), tag))
+                            .collect::<Vec<String>>()
+                            .join("\n");
+                    } else if ui.state == State::SelectTag {
+                        if let Ok(tag) = ui.tags.get_selected() {
+                            text.push(':');
+                            text.push_str(&tag);
+                        }
+                    }
+                    ui.info.set_text("Copying to clipboard");
+                    ui.action = Some(external::Action::CopyToClipboard(text).spawn());
+                }
+                Ok(Key::Ctrl('t')) if opt.read_only => {
+                    ui.info.set_text("read-only mode: the file is not changed")
+                }
+                Ok(Key::Ctrl('t')) => {
+                    let image = match ui.services.extract_repo() {
+                        Err(e) => {
+                            ui.info.set_info(&e);
+                            continue;
+                        }
+                        Ok(image) => image,
+                    };
+                    let current = match crate::repo::split_tag_from_repo(&image) {
+                        Ok((_, tag)) if !tag.is_empty() => tag.to_string(),
+                        _ => {
+                            ui.info.set_text("The image has no tag to track");
+                            continue;
+                        }
+                    };
+                    let tags = ui.tags.get_tags();
+                    let names: Vec<&str> = tags.iter().map(|t| t.get_name()).collect();
+                    match crate::common::version::newest_patch(&current, &names) {
+                        None => ui
+                            .info
+                            .set_text(&format!("No patch of {} in the loaded tags", current)),
+                        Some(tag) if tag == current => ui
+                            .info
+                            .set_text(&format!("{} is the newest patch", current)),
+                        // the list may still show the tags of another repository
+                        Some(tag) => match ui.tags.check_tag(&ui.repo.get(), tag) {
+                            Err(warning) => ui.info.set_text(&warning),
+                            Ok(()) => {
+                                let message =
+                                    format!("Updated {} to the newest patch {}", current, tag);
+                                ui.services.change_current_line(format!(
+                                    "{}:{}",
+                                    ui.repo.get(),
+                                    tag
+                                ));
+                                ui.info.set_text(&message);
+                            }
+                        },
+                    }
+                }
+                Ok(Key::Ctrl('u')) => match ui.services.revert_current_line() {
+                    Err(e) => ui.info.set_info(&e),
+                    Ok(line) => {
+                        let message = format!("Reverted to {}", line.trim());
+                        ui.info.set_text(&message);
+                    }
+                },
+                Ok(Key::Ctrl('k')) => match ui.services.get_current_line() {
+                    Err(e) => ui.info.set_info(&e),
+                    Ok(line) => {
+                        let line = line.to_string();
+                        ui.info.set_text("Copying line to clipboard");
+                        ui.action = Some(external::Action::CopyToClipboard(line).spawn());
+                    }
+                },
+                Ok(Key::Ctrl('o')) => match repository::web_url(&ui.repo.get()) {
+                    Err(e) => ui.info.set_info(&e),
+                    Ok(None) => ui
+                        .info
+                        .set_text(&format!("No web page available for {}", ui.repo.get())),
+                    Ok(Some(url)) => {
+                        ui.info.set_text("Opening browser");
+                        ui.action = Some(external::Action::OpenBrowser(url).spawn());
                     }
-                    Ok(_) => ui.info.set_text("Saved compose file"),
                 },
+                Ok(Key::Ctrl('d')) => {
+                    let description = ui.filter.cycle_since();
+                    ui.tags.set_filter(&ui.filter);
+                    ui.details = ui.tags.create_detail_widget(ui.view);
+                    ui.info.set_text(&description);
+                }
+                Ok(Key::Ctrl('z')) => {
+                    let description = ui.filter.toggle_newest_per_variant();
+                    ui.tags.set_filter(&ui.filter);
+                    ui.details = ui.tags.create_detail_widget(ui.view);
+                    ui.info.set_text(description);
+                }
+                Ok(Key::Ctrl('l')) => {
+                    ui.compact = !ui.compact;
+                    match ui.compact {
+                        true => ui.info.set_text("Compact layout"),
+                        false => ui.info.set_text("Layout with details"),
+                    }
+                }
+                Ok(Key::Alt('n')) if ui.filter.newer_than.is_some() => {
+                    let description = ui.filter.set_newer_than(None);
+                    ui.tags.set_filter(&ui.filter);
+                    ui.details = ui.tags.create_detail_widget(ui.view);
+                    ui.info.set_text(&description);
+                }
+                Ok(Key::Alt('n')) => match ui.services.extract_repo() {
+                    Err(e) => ui.info.set_info(&e),
+                    Ok(image) => match super::spawn_tag_time(&image, &ui.tags, &ui.settings) {
+                        Err(e) => ui
+                            .info
+                            .set_text(&format!("Can't filter by the current tag: {}", e)),
+                        Ok(receiver) => {
+                            ui.info
+                                .set_text(&format!("Looking up when {} was updated", image));
+                            ui.pending_newer = Some(receiver);
+                        }
+                    },
+                },
+                Ok(Key::Alt('o')) => {
+                    let description = ui.filter.cycle_os();
+                    ui.view.os = ui.filter.os;
+                    ui.tags.set_filter(&ui.filter);
+                    ui.details = ui.tags.create_detail_widget(ui.view);
+                    ui.info.set_text(&description);
+                }
+                Ok(Key::Ctrl('a')) => {
+                    ui.view.host_only = !ui.view.host_only;
+                    ui.details = ui.tags.create_detail_widget(ui.view);
+                    match ui.view.host_only {
+                        true => ui
+                            .info
+                            .set_text(&format!("Showing only {}", ui.view.platform)),
+                        false => ui.info.set_text("Showing all platforms"),
+                    }
+                }
+                Ok(Key::Ctrl('g')) => {
+                    match crate::repo::next_namespace(&ui.repo.get(), &opt.config.namespaces) {
+                        None => ui
+                            .info
+                            .set_text("No other namespace, set namespaces in the config"),
+                        Some(repo) => {
+                            ui.repo.set(repo);
+                            ui.tags = ui.fetch.start(ui.repo.get(), &ui.settings);
+                            ui.details = ui.tags.create_detail_widget(ui.view);
+                        }
+                    }
+                }
+                Ok(Key::Ctrl('p')) => {
+                    ui.list_view.platform_count = !ui.list_view.platform_count;
+                    match ui.list_view.platform_count {
+                        true => ui.info.set_text("Showing the number of platforms"),
+                        false => ui.info.set_text("Hiding the number of platforms"),
+                    }
+                }
+                Ok(Key::Ctrl('e')) => {
+                    let message = ui.tags.toggle_grouping();
+                    ui.details = ui.tags.create_detail_widget(ui.view);
+                    ui.info.set_text(message);
+                }
+                Ok(Key::Alt('v')) => {
+                    let message = ui.tags.toggle_sort_by_version();
+                    ui.info.set_text(message);
+                }
+                Ok(Key::Ctrl('n')) => {
+                    let message = ui.layers.toggle();
+                    ui.details = ui.tags.create_detail_widget(ui.view);
+                    ui.info.set_text(&message);
+                }
+                Ok(Key::Alt('b')) => {
+                    let message = ui.build_dates.toggle();
+                    ui.details = ui.tags.create_detail_widget(ui.view);
+                    ui.info.set_text(&message);
+                }
+                Ok(Key::Ctrl('x')) => {
+                    let message = super::export_session(opt, ui.repo.get(), &ui.tags);
+                    ui.info.set_text(&message);
+                }
                 Ok(Key::Ctrl('r')) => {
                     ui.repo.confirm();
-                    ui.tags = tag_list::TagList::with_repo_name(ui.repo.get());
+                    ui.tags = ui.fetch.refresh(ui.repo.get(), &ui.settings);
+                }
+                // the typed repository is not fetched before it is confirmed
+                Ok(Key::F(5)) => match ui.repo.get_confirmed() {
+                    None => ui.info.set_text("No repository to refresh"),
+                    Some(repo) => {
+                        ui.fetch.reload(repo, &ui.settings);
+                        ui.info.set_text("Refreshing…");
+                    }
+                },
+                Ok(Key::Alt('d')) if ui.state == State::SelectTag => {
+                    if opt.read_only {
+                        ui.info.set_text("read-only mode: the file is not changed");
+                        continue;
+                    }
+                    match ui.tags.get_selected_digest() {
+                        None => ui.info.set_info(&tag_list::Error::NoneSelected),
+                        Some((tag, None)) => ui
+                            .info
+                            .set_text(&format!("The registry lists no digest for {}", tag)),
+                        Some((tag, Some(digest))) => {
+                            match ui.tags.check_tag(&ui.repo.get(), &tag) {
+                                Err(warning) => ui.info.set_text(&warning),
+                                Ok(()) => {
+                                    // the digest keeps deployments reproducible even when the tag moves
+                                    ui.services.change_current_line(format!(
+                                        "{}@{}",
+                                        ui.repo.get(),
+                                        digest
+                                    ));
+                                    ui.info
+                                        .set_text(&format!("Pinned to the digest of {}", tag));
+                                }
+                            }
+                        }
+                    }
                 }
                 Ok(Key::Char('\n')) => match ui.state {
                     State::EditRepo => {
                         ui.repo.confirm();
-                        ui.tags = tag_list::TagList::with_repo_name(ui.repo.get());
+                        ui.tags = ui.fetch.start(ui.repo.get(), &ui.settings);
                     }
                     State::SelectTag => {
                         let mut repo = ui.repo.get();
@@ -142,19 +895,92 @@ impl Ui {
                             }
                             Ok(tag) => tag,
                         };
+                        if opt.read_only {
+                            ui.info.set_text("read-only mode: the file is not changed");
+                            continue;
+                        }
+                        // the list may still show the tags of another repository
+                        if let Err(warning) = ui.tags.check_tag(&repo, &tag) {
+                            ui.info.set_text(&warning);
+                            continue;
+                        }
                         repo.push(':');
                         repo.push_str(&tag);
+                        // the selected line is changed even if it is pinned, the others only if not
+                        let current = ui.services.selected_line();
+                        let (pinned, lines): (Vec<usize>, Vec<usize>) = ui
+                            .services
+                            .lines_with_current_repo()
+                            .into_iter()
+                            .partition(|&i| Some(i) != current && ui.services.is_pinned(i));
+                        if opt.update_all && lines.len() > 1 {
+                            let services: Vec<&str> = lines
+                                .iter()
+                                .map(|&i| ui.services.service_name(i).unwrap_or("?"))
+                                .collect();
+                            let warning = match opt.config.tag_policy.is_mutable(&tag) {
+                                true => " (a mutable tag)",
+                                false => "",
+                            };
+                            ui.info.set_text(&format!(
+                                "Update {} lines ({}) to {}{}{}? Return to confirm, any other key to cancel",
+                                lines.len(),
+                                services.join(", "),
+                                repo,
+                                warning,
+                                skipped_pinned(pinned.len())
+                            ));
+                            ui.confirm = Some((lines, repo, pinned.len()));
+                            continue;
+                        }
                         ui.services.change_current_line(repo);
+                        if let Some(warning) = opt.config.tag_policy.warning(&tag) {
+                            ui.info.set_text(&warning);
+                        } else if opt.update_all && !pinned.is_empty() {
+                            ui.info.set_text(&format!(
+                                "Changed the selected line{}",
+                                skipped_pinned(pinned.len())
+                            ));
+                        }
                     }
                     _ => (),
                 },
+                Ok(key @ (Key::Left | Key::Right | Key::Home | Key::End | Key::Delete))
+                    if ui.state == State::EditRepo =>
+                {
+                    ui.repo.handle_input(key)
+                }
+                Ok(key @ (Key::Left | Key::Right)) if ui.state == State::SelectTag => {
+                    ui.tags.handle_input(key);
+                    ui.details = ui.tags.create_detail_widget(ui.view);
+                }
+                Ok(Key::Char(key @ ('+' | '-')))
+                    if ui.state == State::SelectTag && !ui.tags.is_searching() =>
+                {
+                    let message = ui.tags.select_bumped(if key == '+' { 1 } else { -1 });
+                    ui.details = ui.tags.create_detail_widget(ui.view);
+                    ui.info.set_text(&message);
+                }
+                Ok(Key::Char(' ')) if ui.state == State::SelectService => {
+                    match ui.services.toggle_mark() {
+                        None => ui.info.set_text("Only image lines can be marked"),
+                        Some((repo, true)) => ui.info.set_text(&format!(
+                            "Marked {}, Alt+i finds the tags all marked repositories have",
+                            repo
+                        )),
+                        Some((repo, false)) => ui.info.set_text(&format!("Unmarked {}", repo)),
+                    }
+                }
                 Ok(Key::Char(key)) => match ui.state {
                     State::SelectService => (),
                     State::EditRepo => {
                         ui.info.set_text("Editing Repository");
                         ui.repo.handle_input(Key::Char(key));
                     }
-                    State::SelectTag => (),
+                    State::SelectTag => {
+                        ui.tags.handle_input(Key::Char(key));
+                        ui.details = ui.tags.create_detail_widget(ui.view);
+                    }
                 },
                 Ok(Key::Backspace) => match ui.state {
                     State::SelectService => (),
@@ -162,34 +988,21 @@ impl Ui {
                         ui.info.set_text("Editing Repository");
                         ui.repo.handle_input(Key::Backspace);
                     }
-                    State::SelectTag => (),
-                },
-                Ok(Key::Up) => match ui.state {
-                    State::SelectService if ui.services.find_previous_match() => {
-                        match ui.services.extract_repo() {
-                            Err(e) => ui.info.set_info(&format!("{}", e)),
-                            Ok(s) => {
-                                let repo = match repository::check_repo(&s) {
-                                    Err(e) => {
-                                        ui.info.set_info(&format!("{}", e));
-                                        continue;
-                                    }
-                                    Ok(s) => s,
-                                };
-                                ui.repo.set(repo.to_string());
-                                ui.tags = tag_list::TagList::with_repo_name(ui.repo.get());
-                            }
-                        }
+                    State::SelectTag => {
+                        ui.tags.handle_input(Key::Backspace);
+                        ui.details = ui.tags.create_detail_widget(ui.view);
                     }
+                },
+                Ok(Key::Esc) => match ui.state {
                     State::SelectService => (),
-                    State::EditRepo => (),
+                    State::EditRepo => ui.repo.handle_input(Key::Esc),
                     State::SelectTag => {
-                        ui.tags.handle_input(Key::Up);
-                        ui.details = ui.tags.create_detail_widget();
+                        ui.tags.handle_input(Key::Esc);
+                        ui.details = ui.tags.create_detail_widget(ui.view);
                     }
                 },
-                Ok(Key::Down) => match ui.state {
-                    State::SelectService if ui.services.find_next_match() => {
+                Ok(key @ (Key::Up | Key::Down | Key::Home | Key::End)) => match ui.state {
+                    State::SelectService if move_selection(&mut ui.services, key) => {
                         match ui.services.extract_repo() {
                             Err(e) => ui.info.set_info(&format!("{}", e)),
                             Ok(s) => {
@@ -201,24 +1014,45 @@ impl Ui {
                                     Ok(s) => s,
                                 };
                                 ui.repo.set(repo.to_string());
-                                ui.tags = tag_list::TagList::with_repo_name(ui.repo.get());
+                                ui.tags = ui.fetch.start(ui.repo.get(), &ui.settings);
                             }
                         }
                     }
                     State::SelectService => (),
                     State::EditRepo => (),
                     State::SelectTag => {
-                        ui.tags.handle_input(Key::Down);
-                        ui.details = ui.tags.create_detail_widget();
+                        ui.tags.handle_input(key);
+                        ui.details = ui.tags.create_detail_widget(ui.view);
+                        if let Some(text) = ui.tags.describe_selected().filter(|_| opt.accessible) {
+                            ui.info.set_text(&text);
+                        }
                     }
                 },
                 _ => (),
             }
 
             //sleep for 32ms (30 fps)
-            thread::sleep(std::time::Duration::from_millis(32));
+            thread::sleep(Duration::from_millis(32));
         }
 
         terminal.clear().unwrap();
     }
 }
+
+/// moves to another image of the files, returns whether the selected image changed
+fn move_selection(services: &mut service_switcher::ServiceSwitcher, key: Key) -> bool {
+    match key {
+        Key::Up => services.find_previous_match(),
+        Key::Down => services.find_next_match(),
+        Key::Home => services.find_first_match(),
+        _ => services.find_last_match(),
+    }
+}
+
+/// the note about lines which are not updated with the others, because they are pinned
+fn skipped_pinned(count: usize) -> String {
+    match count {
+        0 => String::new(),
+        count => format!(", {} skipped (pinned)", count),
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::repository::Tag;
+
+/// the digests of the tags seen on the last visit of each repo, to notice tags which moved
+pub struct DigestCache {
+    path: Option<PathBuf>,
+    /// digest of each tag of each repo
+    repos: HashMap<String, HashMap<String, String>>,
+}
+
+impl DigestCache {
+    /// the cache in the platform specific cache directory, e.g. ~/.cache/reel-moby/digests.json
+    pub fn load() -> Self {
+        let path = directories::ProjectDirs::from("", "", "reel-moby")
+            .map(|dirs| dirs.cache_dir().join("digests.json"));
+        let repos = path
+            .as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self { path, repos }
+    }
+
+    /// remembers the digests of the tags, returns the names of tags whose digest changed
+    pub fn update(&mut self, repo: &str, tags: &[Tag]) -> Vec<String> {
+        let known = self.repos.entry(repo.to_string()).or_default();
+        let mut moved = vec![];
+        for tag in tags {
+            let digest = match tag.get_digest() {
+                Some(digest) => digest,
+                None => continue,
+            };
+            match known.insert(tag.get_name().to_string(), digest.to_string()) {
+                Some(old) if old != digest => moved.push(tag.get_name().to_string()),
+                _ => (),
+            }
+        }
+        moved
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("{}", e))?;
+        }
+        let json = serde_json::to_string(&self.repos).map_err(|e| format!("{}", e))?;
+        fs::write(path, json).map_err(|e| format!("could not write {}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::DigestCache;
+    use crate::repository::Tag;
+
+    #[test]
+    fn test_update() {
+        let tags = |latest: &str| -> Vec<Tag> {
+            serde_json::from_str(&format!(
+                r#"[
+                    {{"name": "latest", "details": [], "last_updated": null, "digest": "{}"}},
+                    {{"name": "1.25", "details": [], "last_updated": null, "digest": "sha256:b"}},
+                    {{"name": "old", "details": [], "last_updated": null}}
+                ]"#,
+                latest
+            ))
+            .unwrap()
+        };
+        let mut cache = DigestCache {
+            path: None,
+            repos: HashMap::new(),
+        };
+
+        assert!(cache.update("library/nginx", &tags("sha256:a")).is_empty());
+        assert!(cache.update("library/nginx", &tags("sha256:a")).is_empty());
+        assert_eq!(
+            cache.update("library/nginx", &tags("sha256:c")),
+            vec!["latest"]
+        );
+        assert!(cache.update("library/httpd", &tags("sha256:d")).is_empty());
+    }
+}
@@ -0,0 +1,268 @@
+use std::sync::mpsc;
+use std::thread;
+
+use chrono::{DateTime, Utc};
+
+use super::tag_cache::TagCache;
+use crate::repository;
+use crate::widget::tag_list::{Filter, TagList};
+
+/// the tags and when they were cached, if they came from the cache
+type Fetched = (
+    Result<repository::Repo, repository::Error>,
+    Option<DateTime<Utc>>,
+);
+
+/// the frames of the spinner shown while tags are fetched
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// a fetch which didn't arrive yet
+struct Pending {
+    repo: String,
+    receiver: mpsc::Receiver<Fetched>,
+    /// messages while the fetch waits for a registry which limits the requests
+    notices: mpsc::Receiver<String>,
+    /// a notice was shown, which is replaced once the tags arrived
+    waited: bool,
+    /// the tags replace the shown ones of the same repo, which stay on errors
+    reload: bool,
+}
+
+/// the tags of a repository, fetched in the background so the ui keeps drawing
+#[derive(Default)]
+pub struct TagFetch {
+    pending: Option<Pending>,
+    cache: TagCache,
+    /// the frame of the spinner, only the ui thread moves it
+    frame: usize,
+}
+
+impl TagFetch {
+    pub fn new(cache: TagCache) -> Self {
+        Self {
+            pending: None,
+            cache,
+            frame: 0,
+        }
+    }
+
+    /// starts fetching the tags of the repo, returns the list to show meanwhile,
+    /// the result of an earlier fetch which didn't arrive yet is discarded
+    pub fn start(&mut self, repo: String, settings: &repository::Settings) -> TagList {
+        self.pending = Some(self.fetch(repo.clone(), settings, true));
+        TagList::with_status(&format!("Fetching tags of {}…", repo))
+    }
+
+    /// like start, but the tags are fetched from the registry, even when they are cached
+    pub fn refresh(&mut self, repo: String, settings: &repository::Settings) -> TagList {
+        self.pending = Some(self.fetch(repo.clone(), settings, false));
+        TagList::with_status(&format!("Fetching tags of {}…", repo))
+    }
+
+    /// fetches the shown tags again from the registry, e.g. after a tag was pushed,
+    /// they are shown until the new ones arrived
+    pub fn reload(&mut self, repo: String, settings: &repository::Settings) {
+        self.pending = Some(Pending {
+            reload: true,
+            ..self.fetch(repo, settings, false)
+        });
+    }
+
+    fn fetch(&self, repo: String, settings: &repository::Settings, use_cache: bool) -> Pending {
+        let (tx, rx) = mpsc::channel();
+        let (notice_tx, notice_rx) = mpsc::channel();
+        let (name, cache) = (repo.clone(), self.cache.clone());
+        let settings = repository::Settings {
+            wait_notice: Some(notice_tx),
+            ..settings.clone()
+        };
+        thread::spawn(move || {
+            let now = Utc::now();
+            let cached = match use_cache {
+                true => cache.get(&name, now, &settings),
+                false => None,
+            };
+            let fetched = match cached {
+                // only the first page is cached, the next ones come from the registry
+                Some((tags, fetched)) => (Ok(tags), Some(fetched)),
+                None => {
+                    let repo = repository::Repo::new(&name, &settings);
+                    if let Ok(repo) = &repo {
+                        // without a cache the tags are fetched again the next time
+                        let _ = cache.put(&name, repo, now, &settings);
+                    }
+                    (repo, None)
+                }
+            };
+            // the receiver is gone, when another repo was chosen meanwhile
+            let _ = tx.send(fetched);
+        });
+        Pending {
+            repo,
+            receiver: rx,
+            notices: notice_rx,
+            waited: false,
+            reload: false,
+        }
+    }
+
+    /// the next frame of the spinner while tags are fetched, called on each draw
+    pub fn spinner(&mut self) -> Option<char> {
+        self.pending.as_ref()?;
+        self.frame = (self.frame + 1) % SPINNER.len();
+        Some(SPINNER[self.frame])
+    }
+
+    /// the list of the fetched tags, once they arrived, with a message for the info,
+    /// a reload keeps the state of the current list and no list means the current one stays,
+    /// like while the fetch waits for a registry
+    pub fn poll(
+        &mut self,
+        filter: &Filter,
+        current: &TagList,
+    ) -> Option<(Option<TagList>, Option<String>)> {
+        let pending = self.pending.as_mut()?;
+        if let Ok(notice) = pending.notices.try_recv() {
+            pending.waited = true;
+            return Some((None, Some(notice)));
+        }
+        let fetched = match (pending.receiver.try_recv(), pending.reload) {
+            (Err(mpsc::TryRecvError::Empty), _) => return None,
+            (Ok((Ok(repo), _)), true) => {
+                let message = match repo.get_warning() {
+                    Some(warning) => warning.to_string(),
+                    None => format!("Refreshed tags at {}", chrono::Local::now().format("%H:%M")),
+                };
+                let mut list = TagList::with_fetched(pending.repo.clone(), Ok(repo), filter);
+                list.keep_state_of(current);
+                (Some(list), Some(message))
+            }
+            (Ok((Err(e), _)), true) => (None, Some(format!("Refreshing failed: {}", e))),
+            (Ok((fetched, cached)), false) => {
+                // e.g. a failed login, the cached tags have no warnings
+                let warning = fetched.as_ref().ok().and_then(|r| r.get_warning());
+                let message = warning
+                    .map(String::from)
+                    .or_else(|| {
+                        cached.map(|time| {
+                            format!(
+                                "Cached tags from {}, Ctrl+r fetches them again",
+                                time.with_timezone(&chrono::Local).format("%H:%M")
+                            )
+                        })
+                    })
+                    .or_else(|| {
+                        pending
+                            .waited
+                            .then(|| String::from("The registry answered after the wait"))
+                    });
+                (
+                    Some(TagList::with_fetched(pending.repo.clone(), fetched, filter)),
+                    message,
+                )
+            }
+            (Err(mpsc::TryRecvError::Disconnected), true) => {
+                (None, Some(String::from("Refreshing failed")))
+            }
+            (Err(mpsc::TryRecvError::Disconnected), false) => {
+                (Some(TagList::with_status("Fetching tags failed")), None)
+            }
+        };
+        self.pending = None;
+        Some(fetched)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TagFetch;
+    use crate::repository::Settings;
+    use crate::widget::tag_list::{Filter, TagList};
+
+    type Polled = Option<(Option<TagList>, Option<String>)>;
+
+    /// polls until the fetch arrived
+    fn wait(fetch: &mut TagFetch, current: &TagList) -> Polled {
+        for _ in 0..100 {
+            let fetched = fetch.poll(&Filter::default(), current);
+            if fetched.is_some() {
+                return fetched;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        None
+    }
+
+    #[test]
+    fn test_start_again() {
+        // a dry run fails right away instead of sending requests
+        let settings = Settings {
+            dry_run: true,
+            ..Settings::default()
+        };
+        let current = TagList::with_status("");
+        let mut fetch = TagFetch::default();
+        fetch.start("library/nginx".into(), &settings);
+        fetch.start("library/httpd".into(), &settings);
+        assert_eq!(
+            fetch.pending.as_ref().map(|p| p.repo.as_str()),
+            Some("library/httpd")
+        );
+
+        let spinner = fetch.spinner();
+        assert!(spinner.is_some());
+        assert_ne!(fetch.spinner(), spinner);
+
+        assert!(matches!(wait(&mut fetch, &current), Some((Some(_), None))));
+        assert!(fetch.poll(&Filter::default(), &current).is_none());
+        assert_eq!(fetch.spinner(), None);
+    }
+
+    #[test]
+    fn test_reload() {
+        let settings = Settings {
+            dry_run: true,
+            ..Settings::default()
+        };
+        let current = TagList::with_status("");
+        let mut fetch = TagFetch::default();
+        fetch.reload("library/nginx".into(), &settings);
+
+        // the shown tags stay, when the reload failed
+        match wait(&mut fetch, &current) {
+            Some((None, Some(message))) => assert!(message.starts_with("Refreshing failed: ")),
+            _ => panic!("expected a failed reload"),
+        }
+    }
+
+    #[test]
+    fn test_wait_notice() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let (notice_tx, notices) = std::sync::mpsc::channel();
+        let mut fetch = TagFetch {
+            pending: Some(super::Pending {
+                repo: "library/nginx".into(),
+                receiver: rx,
+                notices,
+                waited: false,
+                reload: false,
+            }),
+            ..TagFetch::default()
+        };
+        let current = TagList::with_status("");
+
+        notice_tx.send(String::from("waiting")).unwrap();
+        assert!(matches!(
+            fetch.poll(&Filter::default(), &current),
+            Some((None, Some(message))) if message == "waiting"
+        ));
+        assert!(fetch.poll(&Filter::default(), &current).is_none());
+
+        tx.send((Err(crate::repository::Error::NoTagsFound), None))
+            .unwrap();
+        assert!(matches!(
+            fetch.poll(&Filter::default(), &current),
+            Some((Some(_), Some(message))) if message == "The registry answered after the wait"
+        ));
+    }
+}
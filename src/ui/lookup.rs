@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::thread;
+
+use chrono::{DateTime, Utc};
+
+use crate::repository;
+
+type Fetched<T> = Result<T, String>;
+
+type Fetch<T> = fn(&str, &str, &repository::Settings) -> Result<T, repository::Error>;
+
+/// information about images which needs extra requests,
+/// fetched in the background and cached by repo:tag
+pub struct Lookup<T> {
+    /// what is looked up, e.g. layer counts
+    name: &'static str,
+    fetch: Fetch<T>,
+    enabled: bool,
+    cache: HashMap<String, Fetched<T>>,
+    pending: Option<(String, mpsc::Receiver<Fetched<T>>)>,
+}
+
+impl Lookup<Vec<repository::Layers>> {
+    pub fn layer_counts() -> Self {
+        Self::new("layer counts", repository::fetch_layers)
+    }
+}
+
+impl Lookup<DateTime<Utc>> {
+    pub fn build_dates() -> Self {
+        Self::new("build dates", repository::fetch_build_date)
+    }
+}
+
+impl<T: Clone + Send + 'static> Lookup<T> {
+    fn new(name: &'static str, fetch: Fetch<T>) -> Self {
+        Self {
+            name,
+            fetch,
+            enabled: false,
+            cache: HashMap::new(),
+            pending: None,
+        }
+    }
+
+    /// switch fetching on or off and describe the new state
+    pub fn toggle(&mut self) -> String {
+        self.enabled = !self.enabled;
+        match self.enabled {
+            true => format!("Showing {}, which needs extra requests", self.name),
+            false => format!("Hiding {}", self.name),
+        }
+    }
+
+    /// the information of the image, when it is unknown it is fetched
+    pub fn get(&mut self, repo: &str, tag: &str, settings: &repository::Settings) -> Option<T> {
+        if !self.enabled {
+            return None;
+        }
+        let image = format!("{}:{}", repo, tag);
+        if let Some(fetched) = self.cache.get(&image) {
+            return fetched.clone().ok();
+        }
+
+        match &self.pending {
+            Some((pending, _)) if *pending == image => (),
+            // a newer selection replaces the older fetch
+            _ => {
+                let (tx, rx) = mpsc::channel();
+                let (repo, tag, settings) = (repo.to_string(), tag.to_string(), settings.clone());
+                let fetch = self.fetch;
+                thread::spawn(move || {
+                    let fetched = fetch(&repo, &tag, &settings).map_err(|e| format!("{}", e));
+                    let _ = tx.send(fetched);
+                });
+                self.pending = Some((image, rx));
+            }
+        }
+        None
+    }
+
+    /// cache a finished fetch, returns its error
+    pub fn poll(&mut self) -> Option<String> {
+        let fetched = match &self.pending {
+            Some((_, rx)) => rx.try_recv().ok()?,
+            None => return None,
+        };
+        let (image, _) = self.pending.take()?;
+        let error = fetched
+            .as_ref()
+            .err()
+            .map(|e| format!("No {} for {}: {}", self.name, image, e));
+        self.cache.insert(image, fetched);
+        error
+    }
+}
@@ -1,32 +1,380 @@
 mod default;
+mod digests;
+mod fetch;
+mod lookup;
 mod no_yaml;
+pub mod session;
+mod tag_cache;
 
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc;
 use std::{io, thread};
 
 use crate::Opt;
+use termion::event::Key;
 use termion::input::TermRead;
+use termion::raw::{IntoRawMode, RawTerminal};
+use tui::backend::TermionBackend;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::Terminal;
 
+use crate::common::docker_daemon;
+use crate::repo;
+use crate::repository;
+use crate::widget::file_browser;
 use crate::widget::service_switcher;
+use crate::widget::tag_list;
 
 pub fn create_ui(opt: &Opt) {
-    let service_result = service_switcher::ServiceSwitcher::new(&opt.file);
+    let service_result = service_switcher::ServiceSwitcher::new(&opt.file, opt.with_override);
     match service_result {
         None => no_yaml::NoYaml::run(opt),
         Some(_) => default::Ui::run(opt),
     }
 }
 
+/// the key to move in a list which a vim key stands for, e.g. Down for j
+pub fn vim_motion(key: Key) -> Key {
+    match key {
+        Key::Char('j') => Key::Down,
+        Key::Char('k') => Key::Up,
+        Key::Char('g') => Key::Home,
+        Key::Char('G') => Key::End,
+        key => key,
+    }
+}
+
+/// let the user pick a compose file, when the given one doesn't exist
+pub fn pick_compose_file(missing: &Path) -> Option<PathBuf> {
+    let dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let mut browser = file_browser::FileBrowser::new(
+        &dir,
+        format!("{} not found, pick a file", missing.display()),
+    );
+    let mut terminal = setup_terminal();
+    terminal.clear().unwrap();
+
+    let mut keys = io::stdin().keys();
+    loop {
+        terminal
+            .draw(|rect| {
+                let (list, state) = browser.render();
+                rect.render_stateful_widget(list, rect.size(), state);
+            })
+            .unwrap();
+
+        match keys.next() {
+            Some(Ok(Key::Ctrl('q'))) | Some(Ok(Key::Esc)) | None => break,
+            Some(Ok(key)) => {
+                if let Some(file) = browser.handle_input(key) {
+                    terminal.clear().unwrap();
+                    return Some(file);
+                }
+            }
+            Some(Err(_)) => break,
+        }
+    }
+    terminal.clear().unwrap();
+    None
+}
+
+/// write the current state to the path given by --export-session
+pub fn export_session(opt: &Opt, repo: String, tags: &tag_list::TagList) -> String {
+    let session = session::Session {
+        repo,
+        file: opt.file.first().cloned(),
+        selected: tags.get_selected_name(),
+        tags: tags.get_tags(),
+    };
+    let path = opt
+        .export_session
+        .clone()
+        .unwrap_or_else(|| std::path::PathBuf::from(session::DEFAULT_PATH));
+    match session.save(&path) {
+        Ok(_) => format!("Exported session to {}", path.display()),
+        Err(e) => e,
+    }
+}
+
+/// show the tags of an imported session instead of fetching them
+fn import_session(session: &session::Session, filter: &tag_list::Filter) -> tag_list::TagList {
+    let repo = repository::Repo::from_tags(session.tags.clone());
+    let mut tags = tag_list::TagList::with_tags(repo, filter);
+    if let Some(selected) = &session.selected {
+        tags.select_tag(selected);
+    }
+    tags
+}
+
+/// the cache of fetched tags, without one with --no-cache and in dry runs, which print every request
+fn tag_cache(opt: &Opt) -> tag_cache::TagCache {
+    match opt.no_cache || opt.dry_run || opt.cache_ttl == 0 {
+        true => tag_cache::TagCache::default(),
+        false => tag_cache::TagCache::new(chrono::Duration::minutes(opt.cache_ttl.into())),
+    }
+}
+
+/// remembers the selected tag of each repo and selects it again when the repo is listed again,
+/// returns whether the selection was restored
+pub fn remember_selection(
+    selections: &mut HashMap<String, String>,
+    tags: &mut tag_list::TagList,
+) -> bool {
+    let repo = match tags.get_repo() {
+        Some(repo) => repo.to_string(),
+        None => return false,
+    };
+    match tags.get_selected_name() {
+        Some(tag) => {
+            selections.insert(repo, tag);
+            false
+        }
+        None => match selections.get(&repo) {
+            Some(tag) => {
+                tags.select_tag(tag);
+                tags.get_selected_name().is_some()
+            }
+            None => false,
+        },
+    }
+}
+
+/// when the tag of the image was updated or why it is unknown
+pub type TagTimeFetch = mpsc::Receiver<Result<tag_list::NewerThan, String>>;
+
+/// when the tag of the image was updated, the listed tags are looked through when they are of the same
+/// repo, otherwise the tag is fetched in the background
+pub fn spawn_tag_time(
+    image: &str,
+    tags: &tag_list::TagList,
+    settings: &repository::Settings,
+) -> Result<TagTimeFetch, String> {
+    let (name, tag) = repo::split_tag_from_repo(image).map_err(|e| format!("{}", e))?;
+    if tag.is_empty() {
+        return Err(format!("{} has no tag to compare with", image));
+    }
+    let repo = repository::check_repo(name).map_err(|e| format!("{}", e))?;
+    // the cutoff names the repo like the list, which may be typed without library/
+    let (repo, listed) = match tags.get_repo() {
+        Some(shown) if repository::check_repo(shown).as_ref() == Ok(&repo) => (
+            shown.to_string(),
+            tags.get_tags().into_iter().find(|t| t.get_name() == tag),
+        ),
+        _ => (repo, None),
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let (tag, settings) = (tag.to_string(), settings.clone());
+    match listed {
+        Some(listed) => {
+            let _ = tx.send(newer_than(repo, tag, listed.last_updated()));
+        }
+        None => {
+            thread::spawn(move || {
+                let fetched = match repository::fetch_tag(&repo, &tag, &settings) {
+                    Ok(fetched) => newer_than(repo, tag, fetched.last_updated()),
+                    Err(e) => Err(format!("{} was not found: {}", tag, e)),
+                };
+                // the receiver is gone, when the ui was closed meanwhile
+                let _ = tx.send(fetched);
+            });
+        }
+    }
+    Ok(rx)
+}
+
+/// the cutoff at the tag, when the registry tells when it was updated
+fn newer_than(
+    repo: String,
+    tag: String,
+    updated: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<tag_list::NewerThan, String> {
+    match updated {
+        Some(updated) => Ok(tag_list::NewerThan { repo, tag, updated }),
+        None => Err(format!("The registry has no date for {}", tag)),
+    }
+}
+
+/// compares the digests of newly loaded tags with the last visit of the repo and marks the moved ones
+pub fn check_moved(cache: &mut digests::DigestCache, tags: &mut tag_list::TagList) {
+    let repo = match tags.get_repo() {
+        Some(repo) => repo.to_string(),
+        None => return,
+    };
+    let unchecked = tags.unchecked_tags();
+    if unchecked.is_empty() {
+        return;
+    }
+    let moved = cache.update(&repo, &unchecked);
+    tags.add_moved(moved);
+    // without a cache file tags are compared only within this run
+    let _ = cache.save();
+}
+
+/// a rectangle in the middle of the area with the given percentage of its size
+pub fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_y) / 2),
+                Constraint::Percentage(percent_y),
+                Constraint::Percentage((100 - percent_y) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(area);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_x) / 2),
+                Constraint::Percentage(percent_x),
+                Constraint::Percentage((100 - percent_x) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(vertical[1])[1]
+}
+
+/// switch the terminal into raw mode or exit with a message, when it is not supported
+pub fn setup_terminal() -> Terminal<TermionBackend<RawTerminal<io::Stdout>>> {
+    let stdout = match io::stdout().into_raw_mode() {
+        Ok(stdout) => stdout,
+        Err(e) => {
+            eprintln!(
+                "this terminal doesn't support raw mode ({}); try a different TERM or use --json",
+                e
+            );
+            std::process::exit(1);
+        }
+    };
+
+    match Terminal::new(TermionBackend::new(stdout)) {
+        Ok(terminal) => terminal,
+        Err(e) => {
+            eprintln!("could not set up the terminal: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// check in the background which of the images have a tag older than the given days
+pub fn spawn_stale_check(
+    images: Vec<(usize, String)>,
+    days: i64,
+    settings: repository::Settings,
+) -> mpsc::Receiver<(usize, chrono::Duration)> {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        for (line, image) in images {
+            // images which can't be looked up are ignored
+            let (name, tag) = match repo::split_tag_from_repo(&image) {
+                Ok((name, tag)) if !tag.is_empty() => (name, tag),
+                _ => continue,
+            };
+            let updated = match repository::fetch_tag(name, tag, &settings) {
+                Ok(tag) => match tag.last_updated() {
+                    Some(updated) => updated,
+                    None => continue,
+                },
+                Err(_) => continue,
+            };
+
+            let age = chrono::Utc::now() - updated;
+            if age > chrono::Duration::days(days) && tx.send((line, age)).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+/// ask the docker daemon in the background which containers are running,
+/// nothing is sent when the daemon is not reachable
+pub fn spawn_container_check() -> mpsc::Receiver<Vec<docker_daemon::Container>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        if let Ok(containers) = docker_daemon::running_containers() {
+            let _ = tx.send(containers);
+        }
+    });
+    rx
+}
+
+/// keys read by the input thread, the next key is only read after the last one was handled,
+/// so programs started while handling a key get the terminal input for themselves
+pub struct Input {
+    keys: mpsc::Receiver<Key>,
+    handled: mpsc::Sender<()>,
+    pending: bool,
+}
+
+impl Input {
+    pub fn try_recv(&mut self) -> Result<Key, mpsc::TryRecvError> {
+        if self.pending {
+            // the thread is gone only when the program ends
+            let _ = self.handled.send(());
+            self.pending = false;
+        }
+        let key = self.keys.try_recv();
+        self.pending = key.is_ok();
+        key
+    }
+}
+
+/// the tags all of the repos have or why they could not be fetched
+pub type CommonTagsFetch = mpsc::Receiver<Result<Vec<String>, String>>;
+
+/// fetch the tags of the repos in the background and find the ones all of them have
+pub fn spawn_common_tags(repos: Vec<String>, settings: repository::Settings) -> CommonTagsFetch {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let common = repository::common_tags(&repos, &settings).map_err(|e| format!("{}", e));
+        // the receiver is gone, when the ui was closed meanwhile
+        let _ = tx.send(common);
+    });
+    rx
+}
+
 /// create a thread for catching input and send them to core loop
-pub fn spawn_stdin_channel() -> mpsc::Receiver<termion::event::Key> {
-    let (tx, rx) = mpsc::channel::<termion::event::Key>();
+pub fn spawn_stdin_channel() -> Input {
+    let (tx, rx) = mpsc::channel::<Key>();
+    let (handled_tx, handled_rx) = mpsc::channel::<()>();
 
     thread::spawn(move || loop {
         let stdin = io::stdin();
         for c in stdin.keys() {
             tx.send(c.unwrap()).unwrap();
+            if handled_rx.recv().is_err() {
+                return;
+            }
         }
     });
     thread::sleep(std::time::Duration::from_millis(64));
-    rx
+    Input {
+        keys: rx,
+        handled: handled_tx,
+        pending: false,
+    }
+}
+
+/// open the file in the editor, raw mode is left by dropping the terminal and set up again afterwards
+pub fn edit_file(
+    mut terminal: Terminal<TermionBackend<RawTerminal<io::Stdout>>>,
+    path: &Path,
+) -> (
+    Terminal<TermionBackend<RawTerminal<io::Stdout>>>,
+    Result<(), crate::common::external::Error>,
+) {
+    let _ = terminal.clear();
+    drop(terminal);
+    let result = crate::common::external::edit(path);
+    let mut terminal = setup_terminal();
+    // the editor left its content on the screen
+    let _ = terminal.clear();
+    (terminal, result)
 }
@@ -1,11 +1,13 @@
-use std::{io, thread};
+use std::collections::HashMap;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use termion::event::Key;
-use termion::raw::IntoRawMode;
-use tui::backend::TermionBackend;
 use tui::layout::{Constraint, Direction, Layout};
-use tui::Terminal;
+use tui::widgets::Clear;
 
+use crate::common::external;
+use crate::repository;
 use crate::widget::details;
 use crate::widget::info;
 use crate::widget::repo_entry;
@@ -45,6 +47,22 @@ pub struct NoYaml {
     tags: tag_list::TagList,
     details: details::Details,
     info: info::Info,
+    action: Option<external::Pending>,
+    filter: tag_list::Filter,
+    compact: bool,
+    settings: repository::Settings,
+    view: details::View,
+    layers: super::lookup::Lookup<Vec<crate::repository::Layers>>,
+    build_dates: super::lookup::Lookup<chrono::DateTime<chrono::Utc>>,
+    list_view: tag_list::ListView,
+    cadence: Option<crate::widget::cadence::Cadence>,
+    /// the keys, shown until any key is pressed
+    help: Option<crate::widget::help::Help>,
+    /// the last selected tag of each repo
+    selections: HashMap<String, String>,
+    digests: super::digests::DigestCache,
+    /// the tags of the chosen repo, while they are fetched
+    fetch: super::fetch::TagFetch,
 }
 
 impl NoYaml {
@@ -57,23 +75,55 @@ impl NoYaml {
             tags: tag_list::TagList::with_status("Tags are empty"),
             details: details::Details::new(),
             info: info::Info::new("could not find a docker-compose file"),
+            action: None,
+            filter: tag_list::Filter::new(opt.since)
+                .with_variants(&opt.config.variants)
+                .with_platform(opt.arch),
+            compact: opt.compact,
+            settings: opt.settings(),
+            view: details::View::new().with_platform(opt.platform),
+            layers: super::lookup::Lookup::layer_counts(),
+            build_dates: super::lookup::Lookup::build_dates(),
+            list_view: tag_list::ListView {
+                accessible: opt.accessible,
+                ..tag_list::ListView::default()
+            },
+            cadence: None,
+            help: None,
+            selections: HashMap::new(),
+            digests: super::digests::DigestCache::load(),
+            fetch: super::fetch::TagFetch::new(super::tag_cache(opt)),
         };
 
         // load tags if a repository was given thorugh paramter
-        if opt.repo.is_none() {
-            ui.tags = tag_list::TagList::with_repo_name(ui.repo.get());
+        if let Some(session) = &opt.session {
+            ui.repo.set(session.repo.clone());
+            ui.tags = super::import_session(session, &ui.filter);
+            ui.details = ui.tags.create_detail_widget(ui.view);
+            ui.info.set_text("Imported session");
+        } else if opt.repo.is_some() {
+            ui.tags = ui.fetch.start(ui.repo.get(), &ui.settings);
         }
 
         //setup tui
-        let stdout = io::stdout().into_raw_mode().unwrap();
-        let backend = TermionBackend::new(stdout);
-        let mut terminal = Terminal::new(backend).unwrap();
+        let mut terminal = super::setup_terminal();
 
         //setup input thread
-        let receiver = super::spawn_stdin_channel();
+        let mut receiver = super::spawn_stdin_channel();
+
+        // imported sessions are not refreshed
+        let refresh_idle = opt
+            .refresh_idle
+            .filter(|_| opt.session.is_none())
+            .map(Duration::from_secs);
+        let mut last_input = Instant::now();
+        let mut last_refresh = Instant::now();
 
         //core interaction loop
         'core: loop {
+            //spin while the tags are fetched
+            ui.tags.set_spinner(ui.fetch.spinner());
+
             //draw
             terminal
                 .draw(|rect| {
@@ -90,35 +140,299 @@ impl NoYaml {
                         .split(rect.size());
 
                     rect.render_widget(ui.repo.render(ui.state == State::EditRepo), chunks[0]);
-                    let (list, state) = ui.tags.render(ui.state == State::SelectTag);
-                    let more_chunks = Layout::default()
-                        .direction(Direction::Horizontal)
-                        .constraints([Constraint::Min(15), Constraint::Length(28)].as_ref())
-                        .split(chunks[1]);
-                    rect.render_stateful_widget(list, more_chunks[0], state);
-                    rect.render_widget(ui.details.render(), more_chunks[1]);
+                    let (list, state) = ui.tags.render(ui.state == State::SelectTag, ui.list_view);
+                    if ui.compact {
+                        let more_chunks = Layout::default()
+                            .direction(Direction::Vertical)
+                            .constraints([Constraint::Min(6), Constraint::Length(1)].as_ref())
+                            .split(chunks[1]);
+                        rect.render_stateful_widget(list, more_chunks[0], state);
+                        rect.render_widget(ui.details.render_summary(), more_chunks[1]);
+                    } else {
+                        let more_chunks = Layout::default()
+                            .direction(Direction::Horizontal)
+                            .constraints([Constraint::Min(15), Constraint::Length(28)].as_ref())
+                            .split(chunks[1]);
+                        rect.render_stateful_widget(list, more_chunks[0], state);
+                        rect.render_widget(ui.details.render(), more_chunks[1]);
+                    }
                     rect.render_widget(ui.info.render(), chunks[2]);
+
+                    if ui.info.is_expanded() {
+                        let area = super::centered_rect(80, 60, rect.size());
+                        rect.render_widget(Clear, area);
+                        rect.render_widget(ui.info.render_expanded(), area);
+                    }
+                    if let Some(cadence) = &ui.cadence {
+                        let area = super::centered_rect(80, 60, rect.size());
+                        let data = cadence.data();
+                        rect.render_widget(Clear, area);
+                        rect.render_widget(cadence.render(&data), area);
+                    }
+                    if let Some(help) = &ui.help {
+                        let area = super::centered_rect(80, 80, rect.size());
+                        rect.render_widget(Clear, area);
+                        rect.render_widget(help.render(), area);
+                    }
                 })
                 .unwrap();
 
+            //report finished background actions
+            if let Some(message) = ui.action.as_ref().and_then(|a| a.poll()) {
+                ui.info.set_text(&message);
+                ui.action = None;
+            }
+
+            //add the next page of tags once it arrived
+            if ui.tags.poll_next_page() {
+                ui.details = ui.tags.create_detail_widget(ui.view);
+            }
+
+            //show the tags once they are fetched
+            if let Some((tags, message)) = ui.fetch.poll(&ui.filter, &ui.tags) {
+                if let Some(tags) = tags {
+                    ui.tags = tags;
+                    ui.details = ui.tags.create_detail_widget(ui.view);
+                }
+                if let Some(message) = message {
+                    ui.info.set_text(&message);
+                }
+            }
+
+            //select the tag again which was selected the last time the repo was shown
+            if super::remember_selection(&mut ui.selections, &mut ui.tags) {
+                ui.details = ui.tags.create_detail_widget(ui.view);
+            }
+
+            //mark tags whose digest changed since the last visit
+            super::check_moved(&mut ui.digests, &mut ui.tags);
+
+            //show the layer counts of the selected tag
+            if let Some(message) = ui.layers.poll() {
+                ui.info.set_text(&message);
+            }
+            if let Some(tag) = ui.tags.get_selected_name() {
+                if let Some(layers) = ui.layers.get(&ui.repo.get(), &tag, &ui.settings) {
+                    ui.details.set_layers(layers);
+                }
+            }
+
+            //show when the selected image was built
+            if let Some(message) = ui.build_dates.poll() {
+                ui.info.set_text(&message);
+            }
+            if let Some(tag) = ui.tags.get_selected_name() {
+                if let Some(built) = ui.build_dates.get(&ui.repo.get(), &tag, &ui.settings) {
+                    ui.details.set_built(built);
+                }
+            }
+
+            //refresh the tags when the user was idle for a while
+            if let Some(idle) = refresh_idle {
+                if last_input.elapsed() >= idle
+                    && last_refresh.elapsed() >= idle
+                    && ui.tags.is_loaded()
+                {
+                    // the shown tags stay until the new ones arrived
+                    if let Some(repo) = ui.tags.get_repo() {
+                        ui.fetch.reload(repo.to_string(), &ui.settings);
+                    }
+                    last_refresh = Instant::now();
+                }
+            }
+
             //handle input
-            match receiver.try_recv() {
+            let mut input = receiver.try_recv();
+            // with vim keys the list moves with j, k, g and G, which are typed text elsewhere,
+            // like in the filter of the tags once it was started
+            if opt.config.vim_keys && ui.state == State::SelectTag && !ui.tags.is_searching() {
+                input = input.map(super::vim_motion);
+            }
+            if input.is_ok() {
+                last_input = Instant::now();
+            }
+            match input {
+                Ok(key) if ui.info.is_expanded() => ui.info.handle_input(key),
+                Ok(_) if ui.cadence.is_some() => ui.cadence = None,
+                Ok(_) if ui.help.is_some() => ui.help = None,
+                Ok(Key::Ctrl('b')) => {
+                    ui.cadence = Some(crate::widget::cadence::Cadence::new(&ui.tags.get_tags()));
+                }
+                Ok(Key::Char('?')) => ui.help = Some(crate::widget::help::Help::new(false)),
+                Ok(Key::Ctrl('v')) => ui.info.expand(),
+                Ok(Key::Alt('h')) => ui.info.expand_history(),
+                Ok(Key::Alt('y')) => {
+                    let names = ui.tags.get_visible_names();
+                    match names.len() {
+                        0 => ui.info.set_text("No tags to copy"),
+                        count => {
+                            ui.info
+                                .set_text(&format!("Copying {} tag names to clipboard", count));
+                            ui.action =
+                                Some(external::Action::CopyToClipboard(names.join("\n")).spawn());
+                        }
+                    }
+                }
+                Ok(Key::Alt('c')) => {
+                    let message = ui.tags.toggle_case_sensitive();
+                    ui.details = ui.tags.create_detail_widget(ui.view);
+                    ui.info.set_text(message);
+                }
+                Ok(Key::Ctrl('w')) => match ui.tags.get_url() {
+                    None => ui.info.set_text("No tags were requested yet"),
+                    Some(url) => {
+                        ui.info
+                            .set_text(&format!("Tags were requested from {}, copying it", url));
+                        ui.action =
+                            Some(external::Action::CopyToClipboard(url.to_string()).spawn());
+                    }
+                },
                 Ok(Key::Ctrl('q')) => break 'core,
                 Ok(Key::Char('\t')) => {
                     ui.state.next();
                     ui.info.set_info(&ui.state);
                 }
+                Ok(Key::Ctrl('y')) => {
+                    let mut text = ui.repo.get();
+                    let marked = ui.tags.get_marked();
+                    if !marked.is_empty() {
+                        text = marked
+                            .iter()
+                            .map(|tag| format!("{}:{}", ui.repo.get(), tag))
+                            .collect::<Vec<String>>()
+                            .join("\n");
+                    } else if ui.state == State::SelectTag {
+                        if let Ok(tag) = ui.tags.get_selected() {
+                            text.push(':');
+                            text.push_str(&tag);
+                        }
+                    }
+                    ui.info.set_text("Copying to clipboard");
+                    ui.action = Some(external::Action::CopyToClipboard(text).spawn());
+                }
+                Ok(Key::Ctrl('o')) => match repository::web_url(&ui.repo.get()) {
+                    Err(e) => ui.info.set_info(&e),
+                    Ok(None) => ui
+                        .info
+                        .set_text(&format!("No web page available for {}", ui.repo.get())),
+                    Ok(Some(url)) => {
+                        ui.info.set_text("Opening browser");
+                        ui.action = Some(external::Action::OpenBrowser(url).spawn());
+                    }
+                },
+                Ok(Key::Ctrl('d')) => {
+                    let description = ui.filter.cycle_since();
+                    ui.tags.set_filter(&ui.filter);
+                    ui.details = ui.tags.create_detail_widget(ui.view);
+                    ui.info.set_text(&description);
+                }
+                Ok(Key::Ctrl('z')) => {
+                    let description = ui.filter.toggle_newest_per_variant();
+                    ui.tags.set_filter(&ui.filter);
+                    ui.details = ui.tags.create_detail_widget(ui.view);
+                    ui.info.set_text(description);
+                }
+                Ok(Key::Ctrl('l')) => {
+                    ui.compact = !ui.compact;
+                    match ui.compact {
+                        true => ui.info.set_text("Compact layout"),
+                        false => ui.info.set_text("Layout with details"),
+                    }
+                }
+                Ok(Key::Alt('o')) => {
+                    let description = ui.filter.cycle_os();
+                    ui.view.os = ui.filter.os;
+                    ui.tags.set_filter(&ui.filter);
+                    ui.details = ui.tags.create_detail_widget(ui.view);
+                    ui.info.set_text(&description);
+                }
+                Ok(Key::Ctrl('a')) => {
+                    ui.view.host_only = !ui.view.host_only;
+                    ui.details = ui.tags.create_detail_widget(ui.view);
+                    match ui.view.host_only {
+                        true => ui
+                            .info
+                            .set_text(&format!("Showing only {}", ui.view.platform)),
+                        false => ui.info.set_text("Showing all platforms"),
+                    }
+                }
+                Ok(Key::Ctrl('g')) => {
+                    match crate::repo::next_namespace(&ui.repo.get(), &opt.config.namespaces) {
+                        None => ui
+                            .info
+                            .set_text("No other namespace, set namespaces in the config"),
+                        Some(repo) => {
+                            ui.repo.set(repo);
+                            ui.tags = ui.fetch.start(ui.repo.get(), &ui.settings);
+                            ui.details = ui.tags.create_detail_widget(ui.view);
+                        }
+                    }
+                }
+                Ok(Key::Ctrl('p')) => {
+                    ui.list_view.platform_count = !ui.list_view.platform_count;
+                    match ui.list_view.platform_count {
+                        true => ui.info.set_text("Showing the number of platforms"),
+                        false => ui.info.set_text("Hiding the number of platforms"),
+                    }
+                }
+                Ok(Key::Ctrl('e')) => {
+                    let message = ui.tags.toggle_grouping();
+                    ui.details = ui.tags.create_detail_widget(ui.view);
+                    ui.info.set_text(message);
+                }
+                Ok(Key::Alt('v')) => {
+                    let message = ui.tags.toggle_sort_by_version();
+                    ui.info.set_text(message);
+                }
+                Ok(Key::Ctrl('n')) => {
+                    let message = ui.layers.toggle();
+                    ui.details = ui.tags.create_detail_widget(ui.view);
+                    ui.info.set_text(&message);
+                }
+                Ok(Key::Alt('b')) => {
+                    let message = ui.build_dates.toggle();
+                    ui.details = ui.tags.create_detail_widget(ui.view);
+                    ui.info.set_text(&message);
+                }
+                Ok(Key::Ctrl('x')) => {
+                    let message = super::export_session(opt, ui.repo.get(), &ui.tags);
+                    ui.info.set_text(&message);
+                }
                 Ok(Key::Ctrl('r')) => {
                     ui.repo.confirm();
-                    ui.tags = tag_list::TagList::with_repo_name(ui.repo.get());
+                    ui.tags = ui.fetch.refresh(ui.repo.get(), &ui.settings);
                 }
+                // the typed repository is not fetched before it is confirmed
+                Ok(Key::F(5)) => match ui.repo.get_confirmed() {
+                    None => ui.info.set_text("No repository to refresh"),
+                    Some(repo) => {
+                        ui.fetch.reload(repo, &ui.settings);
+                        ui.info.set_text("Refreshing…");
+                    }
+                },
                 Ok(Key::Char('\n')) => match ui.state {
                     State::EditRepo => {
                         ui.repo.confirm();
-                        ui.tags = tag_list::TagList::with_repo_name(ui.repo.get());
+                        ui.tags = ui.fetch.start(ui.repo.get(), &ui.settings);
                     }
                     State::SelectTag => ui.tags.handle_input(Key::Char('\n')),
                 },
+                Ok(key @ (Key::Left | Key::Right | Key::Home | Key::End | Key::Delete))
+                    if ui.state == State::EditRepo =>
+                {
+                    ui.repo.handle_input(key)
+                }
+                Ok(key @ (Key::Left | Key::Right)) if ui.state == State::SelectTag => {
+                    ui.tags.handle_input(key);
+                    ui.details = ui.tags.create_detail_widget(ui.view);
+                }
+                Ok(Key::Char(key @ ('+' | '-')))
+                    if ui.state == State::SelectTag && !ui.tags.is_searching() =>
+                {
+                    let message = ui.tags.select_bumped(if key == '+' { 1 } else { -1 });
+                    ui.details = ui.tags.create_detail_widget(ui.view);
+                    ui.info.set_text(&message);
+                }
                 Ok(Key::Char(key)) => match ui.state {
                     State::EditRepo => {
                         ui.info.set_text("Editing Repository");
@@ -126,6 +440,7 @@ impl NoYaml {
                     }
                     State::SelectTag => {
                         ui.tags.handle_input(Key::Char(key));
+                        ui.details = ui.tags.create_detail_widget(ui.view);
                     }
                 },
                 Ok(Key::Backspace) => match ui.state {
@@ -133,27 +448,33 @@ impl NoYaml {
                         ui.info.set_text("Editing Repository");
                         ui.repo.handle_input(Key::Backspace);
                     }
-                    State::SelectTag => (),
+                    State::SelectTag => {
+                        ui.tags.handle_input(Key::Backspace);
+                        ui.details = ui.tags.create_detail_widget(ui.view);
+                    }
                 },
-                Ok(Key::Up) => match ui.state {
-                    State::EditRepo => (),
+                Ok(Key::Esc) => match ui.state {
+                    State::EditRepo => ui.repo.handle_input(Key::Esc),
                     State::SelectTag => {
-                        ui.tags.handle_input(Key::Up);
-                        ui.details = ui.tags.create_detail_widget();
+                        ui.tags.handle_input(Key::Esc);
+                        ui.details = ui.tags.create_detail_widget(ui.view);
                     }
                 },
-                Ok(Key::Down) => match ui.state {
+                Ok(key @ (Key::Up | Key::Down | Key::Home | Key::End)) => match ui.state {
                     State::EditRepo => (),
                     State::SelectTag => {
-                        ui.tags.handle_input(Key::Down);
-                        ui.details = ui.tags.create_detail_widget();
+                        ui.tags.handle_input(key);
+                        ui.details = ui.tags.create_detail_widget(ui.view);
+                        if let Some(text) = ui.tags.describe_selected().filter(|_| opt.accessible) {
+                            ui.info.set_text(&text);
+                        }
                     }
                 },
                 _ => (),
             }
 
             //sleep for 32ms (30 fps)
-            thread::sleep(std::time::Duration::from_millis(32));
+            thread::sleep(Duration::from_millis(32));
         }
 
         terminal.clear().unwrap();
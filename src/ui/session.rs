@@ -0,0 +1,70 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::repository::Tag;
+
+/// default file for exporting, when no path was given
+pub const DEFAULT_PATH: &str = "reel-moby-session.json";
+
+/// the state of the ui, which can be exported to reproduce a view without network access
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Session {
+    pub repo: String,
+    pub file: Option<PathBuf>,
+    pub selected: Option<String>,
+    pub tags: Vec<Tag>,
+}
+
+impl Session {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => return Err(format!("could not read {}: {}", path.display(), e)),
+        };
+        match serde_json::from_str(&content) {
+            Ok(session) => Ok(session),
+            Err(e) => Err(format!("invalid session {}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = match serde_json::to_string_pretty(self) {
+            Ok(json) => json,
+            Err(e) => return Err(format!("could not convert session: {}", e)),
+        };
+        match fs::write(path, json) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("could not write {}: {}", path.display(), e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Session;
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join("reel-moby-test-session.json");
+        let session = Session {
+            repo: "library/nginx".into(),
+            file: None,
+            selected: Some("latest".into()),
+            tags: serde_json::from_str(
+                r#"[{"name": "latest", "details": [], "last_updated": null}]"#,
+            )
+            .unwrap(),
+        };
+
+        session.save(&path).unwrap();
+        let loaded = Session::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.repo, "library/nginx");
+        assert_eq!(loaded.selected.as_deref(), Some("latest"));
+        assert_eq!(loaded.tags[0].get_name(), "latest");
+        assert!(Session::load(&path).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::repository::{Repo, Settings, Tag};
+
+/// the first page of tags of a repo and when it was fetched
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    /// rfc 3339, like the dates of the registries
+    fetched: String,
+    tags: Vec<Tag>,
+    /// where the next page is fetched from, missing in the files of older versions
+    #[serde(default)]
+    next_url: Option<String>,
+}
+
+/// the tags of recently fetched repos, so opening reel-moby again doesn't ask the registry again
+#[derive(Clone)]
+pub struct TagCache {
+    /// none when tags are not cached
+    dir: Option<PathBuf>,
+    /// how long cached tags are used
+    ttl: chrono::Duration,
+}
+
+impl Default for TagCache {
+    /// a cache which keeps nothing
+    fn default() -> Self {
+        Self {
+            dir: None,
+            ttl: chrono::Duration::zero(),
+        }
+    }
+}
+
+impl TagCache {
+    /// the cache in the platform specific cache directory, e.g. ~/.cache/reel-moby/tags
+    pub fn new(ttl: chrono::Duration) -> Self {
+        let dir = directories::ProjectDirs::from("", "", "reel-moby")
+            .map(|dirs| dirs.cache_dir().join("tags"));
+        Self { dir, ttl }
+    }
+
+    /// the file of the repo, names like library/nginx are escaped to stay in the directory,
+    /// settings which change the fetched tags, like --query, get a file of their own
+    fn path(&self, repo: &str, settings: &Settings) -> Option<PathBuf> {
+        let mut name = repo
+            .replace('%', "%25")
+            .replace('/', "%2F")
+            .replace(':', "%3A");
+        let defaults = Settings::default();
+        let variant = (
+            settings.page_size,
+            &settings.extra_query,
+            &settings.username,
+            &settings.password,
+            settings.tie_order,
+        );
+        if variant
+            != (
+                defaults.page_size,
+                &defaults.extra_query,
+                &defaults.username,
+                &defaults.password,
+                defaults.tie_order,
+            )
+        {
+            // the hasher has fixed keys, so the same settings find the same file again
+            let mut hasher = DefaultHasher::new();
+            variant.hash(&mut hasher);
+            name = format!("{}-{:016x}", name, hasher.finish());
+        }
+        Some(self.dir.as_ref()?.join(format!("{}.json", name)))
+    }
+
+    /// the cached tags and when they were fetched, none when they are too old,
+    /// a corrupt file is treated like a missing one and replaced by the next fetch,
+    /// the next pages are fetched from the registry with the settings
+    pub fn get(
+        &self,
+        repo: &str,
+        now: DateTime<Utc>,
+        settings: &Settings,
+    ) -> Option<(Repo, DateTime<Utc>)> {
+        let content = fs::read_to_string(self.path(repo, settings)?).ok()?;
+        let entry: Entry = serde_json::from_str(&content).ok()?;
+        let fetched = DateTime::parse_from_rfc3339(&entry.fetched)
+            .ok()?
+            .with_timezone(&Utc);
+        if fetched > now || now - fetched >= self.ttl {
+            return None;
+        }
+        let tags = match &entry.next_url {
+            Some(url) => Repo::with_next_url(entry.tags, url, settings),
+            None => Repo::from_tags(entry.tags),
+        };
+        Some((tags, fetched))
+    }
+
+    /// keeps the first page of tags, with the link to the next one if it can be fetched later,
+    /// the settings are the ones the tags were fetched with
+    pub fn put(
+        &self,
+        repo: &str,
+        tags: &Repo,
+        now: DateTime<Utc>,
+        settings: &Settings,
+    ) -> Result<(), String> {
+        let path = match self.path(repo, settings) {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("{}", e))?;
+        }
+        let entry = Entry {
+            fetched: now.to_rfc3339(),
+            tags: tags.get_tags().to_vec(),
+            next_url: tags.get_next_url().map(String::from),
+        };
+        let json = serde_json::to_string(&entry).map_err(|e| format!("{}", e))?;
+        fs::write(&path, json).map_err(|e| format!("could not write {}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::TagCache;
+    use crate::repository::{Repo, Settings, Tag};
+
+    #[test]
+    fn test_get_and_put() {
+        let dir = std::env::temp_dir().join("reel-moby-test-tag-cache");
+        let cache = TagCache {
+            dir: Some(dir.clone()),
+            ttl: chrono::Duration::hours(1),
+        };
+        let tags: Vec<Tag> =
+            serde_json::from_str(r#"[{"name": "latest", "details": [], "last_updated": null}]"#)
+                .unwrap();
+        let now = chrono::Utc::now();
+        let settings = Settings::default();
+        let next = "https://hub.docker.com/v2/repositories/library/nginx/tags?page=2";
+
+        assert!(cache.get("library/nginx", now, &settings).is_none());
+        cache
+            .put(
+                "library/nginx",
+                &Repo::from_tags(tags.clone()),
+                now,
+                &settings,
+            )
+            .unwrap();
+        assert!(dir.join("library%2Fnginx.json").exists());
+
+        let input: Vec<(&str, chrono::Duration, bool)> = vec![
+            ("library/nginx", chrono::Duration::minutes(59), true),
+            ("library/nginx", chrono::Duration::minutes(61), false),
+            ("library/nginx", chrono::Duration::minutes(-1), false),
+            ("library/httpd", chrono::Duration::zero(), false),
+        ];
+        for i in input {
+            let cached = cache.get(i.0, now + i.1, &settings);
+            assert_eq!(cached.is_some(), i.2, "{} after {}", i.0, i.1);
+            if let Some((repo, fetched)) = cached {
+                assert_eq!(repo.get_tags()[0].get_name(), "latest");
+                assert_eq!(repo.get_next_url(), None);
+                assert_eq!(fetched.timestamp(), now.timestamp());
+            }
+        }
+
+        let repo = Repo::with_next_url(tags.clone(), next, &settings);
+        cache.put("library/nginx", &repo, now, &settings).unwrap();
+        let (repo, _) = cache.get("library/nginx", now, &settings).unwrap();
+        assert_eq!(repo.get_next_url(), Some(next));
+
+        let query = Settings {
+            extra_query: vec![("name".into(), "alpine".into())],
+            ..Settings::default()
+        };
+        assert!(cache.get("library/nginx", now, &query).is_none());
+        cache
+            .put("library/nginx", &Repo::from_tags(vec![]), now, &query)
+            .unwrap();
+        let (repo, _) = cache.get("library/nginx", now, &query).unwrap();
+        assert!(repo.get_tags().is_empty());
+        let (repo, _) = cache.get("library/nginx", now, &settings).unwrap();
+        assert_eq!(repo.get_tags()[0].get_name(), "latest");
+
+        fs::write(dir.join("library%2Fnginx.json"), "{\"fetched\": ").unwrap();
+        assert!(cache.get("library/nginx", now, &settings).is_none());
+        fs::remove_dir_all(&dir).unwrap();
+
+        let disabled = TagCache::default();
+        disabled
+            .put("library/nginx", &repo, now, &settings)
+            .unwrap();
+        assert!(disabled.get("library/nginx", now, &settings).is_none());
+    }
+}
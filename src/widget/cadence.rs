@@ -0,0 +1,105 @@
+use chrono::{DateTime, Datelike, Utc};
+use tui::style::{Color, Style};
+use tui::widgets::{BarChart, Block, Borders};
+
+use crate::repository;
+
+/// months shown at most, ending with the month of the newest tag
+const MAX_MONTHS: i32 = 24;
+
+/// how many tags were updated in each month
+pub struct Cadence {
+    /// label like 24-03 and count of each month
+    months: Vec<(String, u64)>,
+}
+
+impl Cadence {
+    pub fn new(tags: &[repository::Tag]) -> Self {
+        let dates: Vec<DateTime<Utc>> = tags.iter().filter_map(|t| t.last_updated()).collect();
+        Self {
+            months: monthly_counts(&dates),
+        }
+    }
+
+    /// the labels and counts referenced by the chart
+    pub fn data(&self) -> Vec<(&str, u64)> {
+        self.months
+            .iter()
+            .map(|(label, count)| (label.as_str(), *count))
+            .collect()
+    }
+
+    pub fn render<'a>(&self, data: &'a [(&'a str, u64)]) -> BarChart<'a> {
+        let title = match data.is_empty() {
+            true => String::from("Tags per month: no update dates known (any key to close)"),
+            false => format!(
+                "Tags per month of the loaded tags, {} to {} (any key to close)",
+                data[0].0,
+                data[data.len() - 1].0
+            ),
+        };
+        BarChart::default()
+            .block(Block::default().title(title).borders(Borders::ALL))
+            .data(data)
+            .bar_width(5)
+            .bar_gap(1)
+            .bar_style(Style::default().fg(Color::Green))
+            .value_style(Style::default().fg(Color::Black).bg(Color::Green))
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+    }
+}
+
+/// counts the dates per month, months without tags are kept to show gaps
+fn monthly_counts(dates: &[DateTime<Utc>]) -> Vec<(String, u64)> {
+    let month = |d: &DateTime<Utc>| d.year() * 12 + d.month0() as i32;
+    let last = match dates.iter().map(month).max() {
+        Some(last) => last,
+        None => return vec![],
+    };
+    let first = dates
+        .iter()
+        .map(month)
+        .min()
+        .unwrap_or(last)
+        .max(last - MAX_MONTHS + 1);
+
+    (first..=last)
+        .map(|m| {
+            let count = dates.iter().filter(|d| month(d) == m).count() as u64;
+            (format!("{:02}-{:02}", (m / 12) % 100, m % 12 + 1), count)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_monthly_counts() {
+        let dates = vec![
+            Utc.ymd(2023, 11, 3).and_hms(0, 0, 0),
+            Utc.ymd(2024, 1, 10).and_hms(0, 0, 0),
+            Utc.ymd(2024, 1, 20).and_hms(0, 0, 0),
+        ];
+        assert_eq!(
+            super::monthly_counts(&dates),
+            vec![
+                ("23-11".to_string(), 1),
+                ("23-12".to_string(), 0),
+                ("24-01".to_string(), 2)
+            ]
+        );
+
+        assert!(super::monthly_counts(&[]).is_empty());
+
+        let dates = vec![
+            Utc.ymd(2010, 1, 1).and_hms(0, 0, 0),
+            Utc.ymd(2024, 1, 1).and_hms(0, 0, 0),
+        ];
+        let counts = super::monthly_counts(&dates);
+        assert_eq!(counts.len(), 24);
+        assert_eq!(counts[0], ("22-02".to_string(), 0));
+        assert_eq!(counts[23], ("24-01".to_string(), 1));
+    }
+}
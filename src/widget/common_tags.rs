@@ -0,0 +1,60 @@
+use termion::event::Key;
+use tui::style::{Color, Style};
+use tui::widgets::{Block, Borders, List, ListItem, ListState};
+
+/// an overlay listing the tags which all of the repos have
+pub struct CommonTags {
+    repos: Vec<String>,
+    tags: Vec<String>,
+    state: ListState,
+}
+
+impl CommonTags {
+    pub fn new(repos: Vec<String>, tags: Vec<String>) -> Self {
+        let mut state = ListState::default();
+        if !tags.is_empty() {
+            state.select(Some(0));
+        }
+        Self { repos, tags, state }
+    }
+
+    pub fn render(&mut self) -> (List<'_>, &mut ListState) {
+        let items: Vec<ListItem> = match self.tags.is_empty() {
+            true => vec![ListItem::new("No tag is shared by all of them")],
+            false => self
+                .tags
+                .iter()
+                .map(|t| ListItem::new(t.as_str()))
+                .collect(),
+        };
+
+        let items = List::new(items)
+            .block(
+                Block::default()
+                    .title(format!(
+                        "Tags of all of {} (Return Select, Esc Close)",
+                        self.repos.join(", ")
+                    ))
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Green)),
+            )
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .highlight_style(Style::default().bg(Color::Black))
+            .highlight_symbol(">>");
+
+        (items, &mut self.state)
+    }
+
+    /// moves through the tags, returns the picked one
+    pub fn handle_input(&mut self, key: Key) -> Option<String> {
+        let selected = self.state.selected()?;
+        match key {
+            Key::Down => self.state.select(Some((selected + 1) % self.tags.len())),
+            Key::Up if selected == 0 => self.state.select(Some(self.tags.len() - 1)),
+            Key::Up => self.state.select(Some(selected - 1)),
+            Key::Char('\n') => return Some(self.tags[selected].clone()),
+            _ => (),
+        }
+        None
+    }
+}
@@ -0,0 +1,47 @@
+use termion::event::Key;
+use tui::style::{Color, Style};
+use tui::widgets::{Block, Borders, Paragraph};
+
+/// a scrollable overlay asking for a confirmation
+pub struct Confirm {
+    title: String,
+    lines: Vec<String>,
+    scroll: u16,
+}
+
+impl Confirm {
+    pub fn new(title: &str, lines: Vec<String>) -> Self {
+        Self {
+            title: String::from(title),
+            lines,
+            scroll: 0,
+        }
+    }
+
+    pub fn render(&self) -> Paragraph<'_> {
+        Paragraph::new(self.lines.join("\n"))
+            .block(
+                Block::default()
+                    .title(format!(
+                        "{} (Return Confirm, Esc Cancel, ↑ ↓ Scroll)",
+                        self.title
+                    ))
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Green)),
+            )
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .scroll((self.scroll, 0))
+    }
+
+    /// scrolls or returns whether the user confirmed or cancelled
+    pub fn handle_input(&mut self, key: Key) -> Option<bool> {
+        match key {
+            Key::Char('\n') => return Some(true),
+            Key::Esc | Key::Ctrl('q') => return Some(false),
+            Key::Up => self.scroll = self.scroll.saturating_sub(1),
+            Key::Down if (self.scroll as usize) + 1 < self.lines.len() => self.scroll += 1,
+            _ => (),
+        }
+        None
+    }
+}
@@ -1,45 +1,245 @@
+use std::fmt;
+
 use tui::style::{Color, Style};
-use tui::widgets::{Block, Borders, List};
+use tui::widgets::{Block, Borders, List, Paragraph};
 
 use crate::repository;
 
+/// a platform like docker names it, e.g. linux/arm/v6
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Platform {
+    pub os: &'static str,
+    pub arch: &'static str,
+    /// only some architectures like arm have variants
+    pub variant: Option<&'static str>,
+}
+
+impl std::str::FromStr for Platform {
+    type Err = String;
+
+    /// platforms are parsed once at startup, so the parts are kept for the whole run
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&'static str> = Box::leak(s.to_string().into_boxed_str())
+            .split('/')
+            .collect();
+        match parts[..] {
+            [os, arch] if !os.is_empty() && !arch.is_empty() => Ok(Self {
+                os,
+                arch,
+                variant: None,
+            }),
+            [os, arch, variant] if !os.is_empty() && !arch.is_empty() && !variant.is_empty() => {
+                Ok(Self {
+                    os,
+                    arch,
+                    variant: Some(variant),
+                })
+            }
+            _ => Err(format!(
+                "invalid platform '{}', expected os/arch or os/arch/variant",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.variant {
+            Some(variant) => write!(f, "{}/{}/{}", self.os, self.arch, variant),
+            None => write!(f, "{}/{}", self.os, self.arch),
+        }
+    }
+}
+
+impl Platform {
+    /// without a variant all variants of the architecture match
+    pub fn matches(&self, details: &repository::TagDetails) -> bool {
+        details.os.as_deref() == Some(self.os)
+            && details.arch.as_deref() == Some(self.arch)
+            && match self.variant {
+                None => true,
+                Some(variant) => details.variant.as_deref() == Some(variant),
+            }
+    }
+}
+
+/// how the details are shown
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct View {
+    /// only show the platform of this machine or the one given with --platform
+    pub host_only: bool,
+    /// the platform shown with host_only
+    pub platform: Platform,
+    /// only show the platforms of this os, e.g. windows
+    pub os: Option<&'static str>,
+}
+
+impl View {
+    pub fn new() -> Self {
+        Self {
+            host_only: false,
+            platform: host_platform(),
+            os: None,
+        }
+    }
+
+    /// the given platform replaces the one of this machine
+    pub fn with_platform(self, platform: Option<Platform>) -> Self {
+        Self {
+            platform: platform.unwrap_or(self.platform),
+            ..self
+        }
+    }
+}
+
 pub struct Details {
     details: Vec<repository::TagDetails>,
+    /// shown when the host platform was asked for, but the tag has none
+    note: Option<String>,
+    /// layer counts, only known when they were fetched
+    layers: Vec<repository::Layers>,
+    /// when the tag was created and updated, as far as it is known
+    dates: Vec<String>,
+    /// when the image was built, as written in its config
+    built: Option<chrono::DateTime<chrono::Utc>>,
+    /// the digest the tag can be pinned to
+    digest: Option<String>,
 }
 
 impl Details {
     pub fn new() -> Self {
-        Self { details: vec![] }
+        Self {
+            details: vec![],
+            note: None,
+            layers: vec![],
+            dates: vec![],
+            built: None,
+            digest: None,
+        }
     }
 
-    pub fn with_list(details: &[crate::repository::TagDetails]) -> Self {
+    pub fn with_list(details: &[crate::repository::TagDetails], view: View) -> Self {
         let mut detail = Self {
             details: details.to_owned(),
+            note: None,
+            layers: vec![],
+            dates: vec![],
+            built: None,
+            digest: None,
         };
 
-        detail.details.sort_by(|a, b| a.arch.cmp(&b.arch));
+        // some registries list tags without any platform
+        if detail.details.is_empty() {
+            detail.note = Some(String::from("no platform data"));
+        }
+
+        if view.host_only && !detail.details.is_empty() {
+            // images without platform information can't be ruled out
+            detail
+                .details
+                .retain(|d| d.arch.is_none() || view.platform.matches(d));
+            if detail.details.is_empty() {
+                detail.note = Some(format!("not available for {}", view.platform));
+            }
+        }
+
+        if let Some(os) = view.os {
+            // images without an os can't be ruled out
+            detail
+                .details
+                .retain(|d| d.os.is_none() || d.os.as_deref() == Some(os));
+        }
+
+        // the platforms of each os are shown together
+        detail
+            .details
+            .sort_by(|a, b| (&a.os, &a.arch, &a.variant).cmp(&(&b.os, &b.arch, &b.variant)));
         detail.details.dedup();
         detail
     }
 
+    pub fn set_layers(&mut self, layers: Vec<repository::Layers>) {
+        self.layers = layers;
+    }
+
+    pub fn set_dates(&mut self, dates: Vec<String>) {
+        self.dates = dates;
+    }
+
+    pub fn set_built(&mut self, built: chrono::DateTime<chrono::Utc>) {
+        self.built = Some(built);
+    }
+
+    pub fn set_digest(&mut self, digest: Option<&str>) {
+        self.digest = digest.map(String::from);
+    }
+
+    /// the size with the layer count of the platform, when it is known
+    fn format_size_and_layers(&self, details: &repository::TagDetails) -> String {
+        let variant = |v: &Option<String>| v.clone().unwrap_or_default();
+        let layers = self.layers.iter().find(|l| {
+            // layers without platform belong to an image with a single one
+            l.arch.is_none()
+                || (l.arch == details.arch
+                    && l.os == details.os
+                    && variant(&l.variant) == variant(&details.variant))
+        });
+        match layers {
+            Some(layers) => format!(
+                "{}, {} layers",
+                human_size(details.size.unwrap_or_default()),
+                layers.count
+            ),
+            None => human_size(details.size.unwrap_or_default()),
+        }
+    }
+
     pub fn get_details(&self) -> Vec<String> {
-        let mut lines = vec![format!("{:^10}|{:^6}|{:^6}", "ARCH", "OS", "SIZE")];
+        // the registries only report the size of the compressed layers
+        let mut lines = vec![format!("{:^16}| {}", "PLATFORM", "SIZE (compressed)")];
+        if let Some(note) = &self.note {
+            lines.push(note.clone());
+        }
         for d in &self.details {
             lines.push(format!(
-                "{:^10}|{:^6}|{:^6}MB",
-                format!(
-                    "{}{}",
-                    d.arch.clone().unwrap_or_default(),
-                    d.variant.clone().unwrap_or_default()
-                ),
-                d.os.clone().unwrap_or_default(),
-                d.size.unwrap_or_default() / 1024 / 1024,
+                "{:^16}| {}",
+                platform_name(d),
+                self.format_size_and_layers(d),
             ));
         }
+        let mut dates = self.dates.clone();
+        if let Some(built) = self.built {
+            let built = repository::relative_time(built, chrono::Utc::now());
+            dates.insert(0, format!("built {}", built));
+        }
+        if let Some(digest) = &self.digest {
+            dates.push(short_digest(digest));
+        }
+        if !dates.is_empty() {
+            lines.push(String::new());
+            lines.append(&mut dates);
+        }
         lines
     }
 
-    pub fn render(&self) -> List {
+    /// all details in a single line for layouts without the details pane
+    pub fn get_summary(&self) -> String {
+        if let Some(note) = &self.note {
+            return note.clone();
+        }
+        self.details
+            .iter()
+            .map(|d| format!("{} {}", platform_name(d), self.format_size_and_layers(d)))
+            .collect::<Vec<String>>()
+            .join(" | ")
+    }
+
+    pub fn render_summary(&self) -> Paragraph<'_> {
+        Paragraph::new(self.get_summary()).style(Style::default().fg(Color::White).bg(Color::Black))
+    }
+
+    pub fn render(&self) -> List<'_> {
         let items: Vec<tui::widgets::ListItem> = self
             .get_details()
             .iter()
@@ -59,3 +259,245 @@ impl Details {
             .style(Style::default().fg(Color::White).bg(Color::Black))
     }
 }
+
+/// the algorithm and the start of the digest, which fits into the pane, e.g. sha256:0123456789ab
+fn short_digest(digest: &str) -> String {
+    match digest.split_once(':') {
+        Some((algorithm, hash)) => format!("{}:{}", algorithm, &hash[..hash.len().min(12)]),
+        None => digest.to_string(),
+    }
+}
+
+/// the size in the largest binary unit it has at least one of, e.g. 45.2 MiB
+fn human_size(bytes: usize) -> String {
+    const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// the platform as os/arch/variant, the variant is left out when there is none
+fn platform_name(details: &repository::TagDetails) -> String {
+    let parts = [&details.os, &details.arch, &details.variant];
+    parts
+        .iter()
+        .filter_map(|p| p.as_deref())
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<&str>>()
+        .join("/")
+}
+
+/// the os and architecture of this machine in the names docker uses
+fn host_platform() -> Platform {
+    let os = match std::env::consts::OS {
+        // docker desktop runs linux images in a vm
+        "macos" => "linux",
+        os => os,
+    };
+    let arch = match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        "x86" => "386",
+        "powerpc64" => "ppc64le",
+        arch => arch,
+    };
+    Platform {
+        os,
+        arch,
+        variant: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Details, View};
+    use crate::repository::TagDetails;
+
+    #[test]
+    fn test_human_size() {
+        let input: Vec<(usize, &str)> = vec![
+            (0, "0 B"),
+            (1023, "1023 B"),
+            (1024, "1.0 KiB"),
+            (47_395_635, "45.2 MiB"),
+            (3 * 1024 * 1024 * 1024, "3.0 GiB"),
+            (2048 * 1024 * 1024 * 1024, "2048.0 GiB"),
+        ];
+
+        for i in input {
+            assert_eq!(super::human_size(i.0), i.1);
+        }
+    }
+
+    #[test]
+    fn test_short_digest() {
+        let mut details = Details::with_list(&[], View::new());
+        details.set_digest(Some(
+            "sha256:0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef",
+        ));
+        assert_eq!(details.get_details().last().unwrap(), "sha256:0123456789ab");
+        assert_eq!(super::short_digest("sha256:abc"), "sha256:abc");
+    }
+
+    #[test]
+    fn test_no_platform_data() {
+        let details = Details::with_list(&[], View::new());
+        assert_eq!(details.get_summary(), "no platform data");
+        assert_eq!(details.get_details()[1], "no platform data");
+        assert!(!Details::new()
+            .get_details()
+            .iter()
+            .any(|l| l == "no platform data"));
+    }
+
+    #[test]
+    fn test_layers() {
+        let platform = |arch: &str, variant: Option<&str>| TagDetails {
+            arch: Some(arch.into()),
+            variant: variant.map(String::from),
+            os: Some("linux".into()),
+            size: Some(1024 * 1024),
+            digest: None,
+        };
+        let layers = |arch: &str, variant: Option<&str>, count| crate::repository::Layers {
+            os: Some("linux".into()),
+            arch: Some(arch.into()),
+            variant: variant.map(String::from),
+            count,
+        };
+        let mut details = Details::with_list(
+            &[platform("amd64", Some("")), platform("arm", Some("v7"))],
+            View::new(),
+        );
+        details.set_layers(vec![layers("amd64", None, 7)]);
+
+        assert_eq!(
+            details.format_size_and_layers(&platform("amd64", Some(""))),
+            "1.0 MiB, 7 layers"
+        );
+        assert_eq!(
+            details.format_size_and_layers(&platform("arm", Some("v7"))),
+            "1.0 MiB"
+        );
+    }
+
+    #[test]
+    fn test_host_only() {
+        let super::Platform { os, arch, .. } = super::host_platform();
+        let platform = |os: &str, arch: &str| TagDetails {
+            arch: Some(arch.into()),
+            variant: None,
+            os: Some(os.into()),
+            size: None,
+            digest: None,
+        };
+        let view = View {
+            host_only: true,
+            ..View::new()
+        };
+
+        let details = Details::with_list(&[platform(os, arch), platform("plan9", "mips")], view);
+        assert_eq!(details.details, vec![platform(os, arch)]);
+        assert_eq!(details.note, None);
+
+        let details = Details::with_list(&[platform("plan9", "mips")], view);
+        assert!(details.details.is_empty());
+        assert!(details.note.unwrap().starts_with("not available for"));
+    }
+
+    #[test]
+    fn test_platform() {
+        let platform = |arch: &str, variant: Option<&str>| TagDetails {
+            arch: Some(arch.into()),
+            variant: variant.map(String::from),
+            os: Some("linux".into()),
+            size: None,
+            digest: None,
+        };
+        let all = [
+            platform("arm", Some("v7")),
+            platform("amd64", Some("")),
+            platform("arm", Some("v6")),
+        ];
+        let input: Vec<(&str, Vec<TagDetails>)> = vec![
+            ("linux/arm/v6", vec![platform("arm", Some("v6"))]),
+            (
+                "linux/arm",
+                vec![platform("arm", Some("v6")), platform("arm", Some("v7"))],
+            ),
+            ("linux/amd64", vec![platform("amd64", Some(""))]),
+            ("windows/amd64", vec![]),
+        ];
+
+        for i in input {
+            let view = View {
+                host_only: true,
+                platform: i.0.parse().unwrap(),
+                ..View::new()
+            };
+            assert_eq!(Details::with_list(&all, view).details, i.1, "{}", i.0);
+        }
+
+        let details = Details::with_list(&all, View::new());
+        assert_eq!(
+            details.get_summary(),
+            "linux/amd64 0 B | linux/arm/v6 0 B | linux/arm/v7 0 B"
+        );
+    }
+
+    #[test]
+    fn test_parse_platform() {
+        let input: Vec<(&str, Option<&str>)> = vec![
+            ("linux/arm/v6", Some("linux/arm/v6")),
+            ("linux/amd64", Some("linux/amd64")),
+            ("linux", None),
+            ("linux//v7", None),
+            ("linux/arm/v7/extra", None),
+        ];
+
+        for i in input {
+            let platform = i.0.parse::<super::Platform>().ok().map(|p| p.to_string());
+            assert_eq!(platform.as_deref(), i.1, "{}", i.0);
+        }
+    }
+
+    #[test]
+    fn test_os() {
+        let platform = |os: &str, arch: &str| TagDetails {
+            arch: Some(arch.into()),
+            variant: None,
+            os: Some(os.into()),
+            size: None,
+            digest: None,
+        };
+        let all = [
+            platform("windows", "amd64"),
+            platform("linux", "arm64"),
+            platform("linux", "amd64"),
+        ];
+
+        let details = Details::with_list(&all, View::new());
+        assert_eq!(
+            details.details,
+            vec![
+                platform("linux", "amd64"),
+                platform("linux", "arm64"),
+                platform("windows", "amd64")
+            ]
+        );
+
+        let view = View {
+            os: Some("windows"),
+            ..View::new()
+        };
+        let details = Details::with_list(&all, view);
+        assert_eq!(details.details, vec![platform("windows", "amd64")]);
+    }
+}
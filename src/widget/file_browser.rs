@@ -0,0 +1,148 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use termion::event::Key;
+use tui::style::{Color, Style};
+use tui::widgets::{Block, Borders, List, ListItem, ListState};
+
+/// a browser through directories to pick a docker-compose file
+pub struct FileBrowser {
+    dir: PathBuf,
+    /// parent, directories and compose files of the current directory
+    entries: Vec<PathBuf>,
+    state: ListState,
+    title: String,
+}
+
+impl FileBrowser {
+    pub fn new(dir: &Path, title: String) -> Self {
+        let mut browser = Self {
+            dir: PathBuf::new(),
+            entries: vec![],
+            state: ListState::default(),
+            title,
+        };
+        browser.change_dir(dir.to_path_buf());
+        browser
+    }
+
+    fn change_dir(&mut self, dir: PathBuf) {
+        let mut dirs = vec![];
+        let mut files = vec![];
+        if let Ok(read) = fs::read_dir(&dir) {
+            for entry in read.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    dirs.push(path);
+                } else if is_compose_file(&path) {
+                    files.push(path);
+                }
+            }
+        }
+        dirs.sort();
+        files.sort();
+
+        self.entries = vec![];
+        if let Some(parent) = dir.parent() {
+            self.entries.push(parent.to_path_buf());
+        }
+        self.entries.append(&mut dirs);
+        self.entries.append(&mut files);
+        self.state.select(Some(0));
+        self.dir = dir;
+    }
+
+    pub fn render(&mut self) -> (List<'_>, &mut ListState) {
+        let has_parent = self.dir.parent().is_some();
+        let items: Vec<ListItem> = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(i, path)| {
+                let name = match path.file_name() {
+                    _ if i == 0 && has_parent => String::from(".."),
+                    Some(name) => name.to_string_lossy().to_string(),
+                    None => path.display().to_string(),
+                };
+                match path.is_dir() {
+                    true => ListItem::new(format!("{}/", name)),
+                    false => ListItem::new(name),
+                }
+            })
+            .collect();
+
+        let items = List::new(items)
+            .block(
+                Block::default()
+                    .title(format!("{} ({})", self.title, self.dir.display()))
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Green)),
+            )
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .highlight_style(Style::default().bg(Color::Black))
+            .highlight_symbol(">>");
+
+        (items, &mut self.state)
+    }
+
+    /// moves through the entries, returns the file when one is picked
+    pub fn handle_input(&mut self, key: Key) -> Option<PathBuf> {
+        match key {
+            Key::Down => self.next(),
+            Key::Up => self.previous(),
+            Key::Char('\n') => {
+                let path = self.entries.get(self.state.selected()?)?.clone();
+                match path.is_dir() {
+                    true => self.change_dir(path),
+                    false => return Some(path),
+                }
+            }
+            _ => (),
+        }
+        None
+    }
+
+    fn next(&mut self) {
+        match self.state.selected() {
+            Some(i) if i + 1 < self.entries.len() => self.state.select(Some(i + 1)),
+            _ => self.state.select(Some(0)),
+        }
+    }
+
+    fn previous(&mut self) {
+        match self.state.selected() {
+            Some(i) if i > 0 => self.state.select(Some(i - 1)),
+            _ => self
+                .state
+                .select(Some(self.entries.len().saturating_sub(1))),
+        }
+    }
+}
+
+/// files which look like a docker-compose file or a podman quadlet
+fn is_compose_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("yml") | Some("yaml") | Some("container")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    #[test]
+    fn test_is_compose_file() {
+        let input: Vec<(&str, bool)> = vec![
+            ("docker-compose.yml", true),
+            ("compose/prod.yaml", true),
+            ("nginx.container", true),
+            ("README.md", false),
+            ("yml", false),
+        ];
+
+        for i in input {
+            assert_eq!(super::is_compose_file(Path::new(i.0)), i.1, "{}", i.0);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use termion::event::Key;
+use tui::style::{Color, Style};
+use tui::widgets::{Block, Borders, List, ListItem, ListState};
+
+/// an overlay listing the loaded files to jump to one of them
+pub struct FilePicker {
+    /// the name of each file and whether it has unsaved changes
+    files: Vec<(String, bool)>,
+    state: ListState,
+}
+
+impl FilePicker {
+    pub fn new(files: Vec<(String, bool)>) -> Self {
+        let mut state = ListState::default();
+        state.select(Some(0));
+        Self { files, state }
+    }
+
+    pub fn render(&mut self) -> (List<'_>, &mut ListState) {
+        let items: Vec<ListItem> = self
+            .files
+            .iter()
+            .map(|(name, changed)| match changed {
+                true => ListItem::new(format!("*{}*   (unsaved changes)", name)),
+                false => ListItem::new(name.clone()),
+            })
+            .collect();
+
+        let items = List::new(items)
+            .block(
+                Block::default()
+                    .title("Jump to file (Return Select, Esc Cancel)")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Green)),
+            )
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .highlight_style(Style::default().bg(Color::Black))
+            .highlight_symbol(">>");
+
+        (items, &mut self.state)
+    }
+
+    /// moves through the files, returns the index of the picked one
+    pub fn handle_input(&mut self, key: Key) -> Option<usize> {
+        let selected = self.state.selected().unwrap_or(0);
+        match key {
+            Key::Down => self.state.select(Some((selected + 1) % self.files.len())),
+            Key::Up if selected == 0 => self.state.select(Some(self.files.len() - 1)),
+            Key::Up => self.state.select(Some(selected - 1)),
+            Key::Char('\n') => return Some(selected),
+            _ => (),
+        }
+        None
+    }
+}
@@ -0,0 +1,172 @@
+use tui::style::{Color, Style};
+use tui::widgets::{Block, Borders, List, ListItem};
+
+/// a key and what it does
+pub struct Binding {
+    pub key: &'static str,
+    pub action: &'static str,
+    /// only bound when a compose file is opened
+    pub needs_file: bool,
+}
+
+const fn bind(key: &'static str, action: &'static str) -> Binding {
+    Binding {
+        key,
+        action,
+        needs_file: false,
+    }
+}
+
+const fn bind_file(key: &'static str, action: &'static str) -> Binding {
+    Binding {
+        key,
+        action,
+        needs_file: true,
+    }
+}
+
+/// all keys of the ui, the tests check them against the keys the ui handles
+pub const KEYMAP: &[Binding] = &[
+    bind("?", "show this help"),
+    bind(
+        "Tab",
+        "switch between the images, the repository and the tags",
+    ),
+    bind(
+        "Up/Down",
+        "select an image or a tag, like j/k with vim_keys",
+    ),
+    bind(
+        "Home/End",
+        "select the first or last entry, like g/G with vim_keys",
+    ),
+    bind(
+        "Return",
+        "fetch the tags of the repository or use the selected tag",
+    ),
+    bind(
+        "Ctrl+r",
+        "fetch the tags of the typed repository, also when they are cached",
+    ),
+    bind("F5", "fetch the shown tags again, keeping the selection"),
+    bind("Esc", "clear the filter or undo editing the repository"),
+    bind(
+        "Space",
+        "mark the selected tag, or the image line to compare with Alt+i",
+    ),
+    bind("+/-", "select the next or previous version of the tag"),
+    bind("Left/Right", "collapse or expand a group of tags"),
+    bind("Ctrl+q", "quit"),
+    bind_file("Ctrl+s", "save the changes"),
+    bind_file("Alt+s", "save the changes to another path"),
+    bind_file("Ctrl+u", "revert the selected line"),
+    bind_file("Ctrl+t", "update the image to the newest patch"),
+    bind_file("Alt+d", "pin the image to the digest of the selected tag"),
+    bind_file("Ctrl+k", "copy the selected line"),
+    bind_file("Ctrl+f", "jump to another loaded file"),
+    bind_file("Alt+e", "edit the file in the editor"),
+    bind_file("Alt+i", "find the tags all marked or related images have"),
+    bind_file("Alt+n", "only show tags newer than the one in the file"),
+    bind("Ctrl+y", "copy the image with the selected or marked tags"),
+    bind("Alt+y", "copy the names of the listed tags"),
+    bind("Ctrl+w", "copy the url the tags were requested from"),
+    bind("Ctrl+o", "open the repository in the browser"),
+    bind("Ctrl+g", "switch to the next namespace of the config"),
+    bind("Ctrl+d", "cycle the age of the shown tags"),
+    bind("Ctrl+z", "only show the newest tag of each variant"),
+    bind("Alt+o", "cycle the os of the shown tags"),
+    bind("Alt+c", "filter case sensitive or not"),
+    bind("Alt+v", "sort the tags by version"),
+    bind("Ctrl+e", "group tags of the same image"),
+    bind("Ctrl+a", "only show the platform of this machine"),
+    bind("Ctrl+p", "show the number of platforms"),
+    bind("Ctrl+n", "show the number of layers"),
+    bind("Alt+b", "show when the image was built"),
+    bind("Ctrl+b", "show the tags per month"),
+    bind(
+        "Ctrl+l",
+        "switch between the compact and the detailed layout",
+    ),
+    bind("Ctrl+v", "expand the message"),
+    bind("Alt+h", "show the earlier messages"),
+    bind("Ctrl+x", "export the session"),
+];
+
+/// the keys of the ui in a popup
+pub struct Help {
+    with_file: bool,
+}
+
+impl Help {
+    /// without a file, only the keys which work without one are listed
+    pub fn new(with_file: bool) -> Self {
+        Self { with_file }
+    }
+
+    fn lines(&self) -> Vec<String> {
+        KEYMAP
+            .iter()
+            .filter(|b| self.with_file || !b.needs_file)
+            .map(|b| format!("{:>10}  {}", b.key, b.action))
+            .collect()
+    }
+
+    pub fn render(&self) -> List<'_> {
+        let items: Vec<ListItem> = self
+            .lines()
+            .into_iter()
+            .map(|l| ListItem::new(l).style(Style::default().fg(Color::White).bg(Color::Black)))
+            .collect();
+
+        List::new(items)
+            .block(
+                Block::default()
+                    .title("Keys (any key to close)")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Green)),
+            )
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::KEYMAP;
+
+    /// the Ctrl and Alt keys a ui handles, like Ctrl+q
+    fn handled_keys(source: &str) -> HashSet<String> {
+        let regex = regex::Regex::new(r"Key::(Ctrl|Alt)\('(.)'\)").unwrap();
+        regex
+            .captures_iter(source)
+            .map(|caps| format!("{}+{}", &caps[1], &caps[2]))
+            .collect()
+    }
+
+    #[test]
+    fn test_keymap_matches_ui() {
+        let uis = vec![
+            (include_str!("../ui/default.rs"), true),
+            (include_str!("../ui/no_yaml.rs"), false),
+        ];
+
+        for (source, with_file) in uis {
+            let handled = handled_keys(source);
+            let listed: HashSet<String> = KEYMAP
+                .iter()
+                .filter(|b| with_file || !b.needs_file)
+                .filter(|b| b.key.starts_with("Ctrl+") || b.key.starts_with("Alt+"))
+                .map(|b| b.key.to_string())
+                .collect();
+            assert_eq!(handled, listed, "with file: {}", with_file);
+        }
+    }
+
+    #[test]
+    fn test_unique_keys() {
+        let keys: HashSet<&str> = KEYMAP.iter().map(|b| b.key).collect();
+        assert_eq!(keys.len(), KEYMAP.len());
+        assert!(super::Help::new(true).lines().len() > super::Help::new(false).lines().len());
+    }
+}
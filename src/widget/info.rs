@@ -1,39 +1,159 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Local};
+use termion::event::Key;
 use tui::style::{Color, Style};
-use tui::widgets::{Block, List, ListItem};
+use tui::widgets::{Block, Borders, List, ListItem, Paragraph, Wrap};
 
 pub struct Info {
     info: String,
     keys: String,
+    /// shown in front of the keys, e.g. for read-only mode
+    mode: Option<String>,
+    /// show the whole info in an overlay
+    expanded: bool,
+    /// show the history instead of the info in the overlay
+    show_history: bool,
+    scroll: u16,
+    /// the recent messages, the newest last
+    history: VecDeque<(DateTime<Local>, String)>,
 }
 
+/// messages kept in the history
+const HISTORY_SIZE: usize = 200;
+
 impl Info {
     pub fn new(info: &str) -> Self {
         Self {
             info: String::from(info),
             keys: String::from(
-                "Tab Cycle widgets   C-s Save   C-r Reload   C-q Quit   ↑ ↓ Select tags or image line   Return Select current selection",
+                "Tab Cycle widgets   C-s Save   C-u Revert line   C-f Jump to file   M-i Common tags   M-e Edit file   C-t Newest patch   C-r Reload   F5 Refresh tags   C-g Next namespace   C-d Filter by age   C-z Newest per variant   M-n Newer than current   C-l Layout   C-a Host platform   M-o Filter by os   C-n Layer counts   M-b Build dates   C-p Platform count   C-e Group by digest   C-b Tags per month   Space Mark tag   C-y Copy   M-y Copy tag names   C-k Copy line   C-w Copy API url   C-o Open in browser   C-x Export session   C-v View message   M-h Message history   C-q Quit   ↑ ↓ Select tags or image line   Return Select current selection   + - Next or previous version   Type in tags to filter, Return picks a unique match   M-c Case sensitive filter",
             ),
+            mode: None,
+            expanded: false,
+            show_history: false,
+            scroll: 0,
+            history: VecDeque::new(),
         }
     }
 
-    pub fn render(&self) -> List {
-        let items = vec![
-            ListItem::new(self.info.clone()),
-            ListItem::new(self.keys.clone()),
-        ];
+    pub fn render(&self) -> List<'_> {
+        let keys = match &self.mode {
+            None => self.keys.clone(),
+            Some(mode) => format!("[{}]   {}", mode, self.keys),
+        };
+        let items = vec![ListItem::new(self.info.clone()), ListItem::new(keys)];
         List::new(items)
             .block(Block::default())
             .style(Style::default().fg(Color::White).bg(Color::Black))
             .highlight_style(Style::default().bg(Color::Black))
     }
 
+    /// the whole info or the history wrapped into multiple lines
+    pub fn render_expanded(&self) -> Paragraph<'_> {
+        let (title, text) = match self.show_history {
+            true => (
+                "Messages (↑ ↓ Scroll, any other key to close)",
+                self.history_text(),
+            ),
+            false => (
+                "Message (↑ ↓ Scroll, any other key to close)",
+                self.info.clone(),
+            ),
+        };
+        Paragraph::new(text)
+            .block(Block::default().title(title).borders(Borders::ALL))
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0))
+    }
+
+    pub fn is_expanded(&self) -> bool {
+        self.expanded
+    }
+
+    /// show the whole info in an overlay
+    pub fn expand(&mut self) {
+        self.expanded = true;
+        self.show_history = false;
+        self.scroll = 0;
+    }
+
+    /// show the recent messages in an overlay, the newest first
+    pub fn expand_history(&mut self) {
+        self.expanded = true;
+        self.show_history = true;
+        self.scroll = 0;
+    }
+
+    fn history_text(&self) -> String {
+        match self.history.is_empty() {
+            true => String::from("No messages yet"),
+            false => self
+                .history
+                .iter()
+                .rev()
+                .map(|(time, message)| format!("{}  {}", time.format("%H:%M:%S"), message))
+                .collect::<Vec<String>>()
+                .join("\n"),
+        }
+    }
+
+    /// remember the message, repeated ones only once
+    fn remember(&mut self) {
+        if self.history.back().map(|(_, m)| m) == Some(&self.info) {
+            return;
+        }
+        if self.history.len() >= HISTORY_SIZE {
+            self.history.pop_front();
+        }
+        self.history.push_back((Local::now(), self.info.clone()));
+    }
+
+    /// scroll the expanded info or close it
+    pub fn handle_input(&mut self, key: Key) {
+        match key {
+            Key::Up => self.scroll = self.scroll.saturating_sub(1),
+            Key::Down => self.scroll = self.scroll.saturating_add(1),
+            _ => self.expanded = false,
+        }
+    }
+
+    /// set a permanent mode to display in front of the keys
+    pub fn set_mode(&mut self, mode: &str) {
+        self.mode = Some(String::from(mode));
+    }
+
     /// set a text to display
     pub fn set_text(&mut self, info: &str) {
         self.info = String::from(info);
+        self.remember();
     }
 
     /// print a text to display
     pub fn set_info(&mut self, text: &dyn std::fmt::Display) {
         self.info = format!("{}", text);
+        self.remember();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Info;
+
+    #[test]
+    fn test_history() {
+        let mut info = Info::new("start");
+        info.set_text("Saved compose file");
+        info.set_text("Saved compose file");
+        info.set_info(&"Copying to clipboard");
+        let messages: Vec<&str> = info.history.iter().map(|(_, m)| m.as_str()).collect();
+        assert_eq!(messages, vec!["Saved compose file", "Copying to clipboard"]);
+
+        for i in 0..super::HISTORY_SIZE {
+            info.set_text(&format!("message {}", i));
+        }
+        assert_eq!(info.history.len(), super::HISTORY_SIZE);
+        assert_eq!(info.history[0].1, "message 0");
     }
 }
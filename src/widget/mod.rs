@@ -1,4 +1,10 @@
+pub mod cadence;
+pub mod common_tags;
+pub mod confirm;
 pub mod details;
+pub mod file_browser;
+pub mod file_picker;
+pub mod help;
 pub mod info;
 pub mod repo_entry;
 pub mod service_switcher;
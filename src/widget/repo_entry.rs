@@ -1,23 +1,47 @@
 use termion::event::Key;
 use tui::layout::Alignment;
-use tui::style::{Color, Style};
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
 use tui::widgets::{Block, Borders, Paragraph};
 
+/// the title of the entry for repositories
+const REPO_TITLE: &str = "Repository";
+
+/// the text of the entry before a repository was given
+const DEFAULT_TEXT: &str = "enter a repository here or select one from file widget";
+
 pub struct RepoEntry {
+    title: &'static str,
     text: String,
     old_text: String,
     changed: bool,
     default_text: bool,
+    /// position in characters where typed text is inserted
+    cursor: usize,
 }
 
 impl RepoEntry {
     pub fn new(text: Option<&str>) -> Self {
-        let default_text = "enter a repository here or select one from file widget";
+        let default_text = DEFAULT_TEXT;
         Self {
+            title: REPO_TITLE,
             text: String::from(text.unwrap_or(default_text)),
             old_text: String::from(text.unwrap_or(default_text)),
             changed: false,
             default_text: text.is_none(),
+            cursor: text.unwrap_or(default_text).chars().count(),
+        }
+    }
+
+    /// an entry with the same editing for other text like a path
+    pub fn prompt(title: &'static str, text: &str) -> Self {
+        Self {
+            title,
+            text: String::from(text),
+            old_text: String::from(text),
+            changed: false,
+            default_text: false,
+            cursor: text.chars().count(),
         }
     }
 
@@ -25,16 +49,38 @@ impl RepoEntry {
         self.text.clone()
     }
 
+    /// the text as it was when it was confirmed the last time, none before a repository was given
+    pub fn get_confirmed(&self) -> Option<String> {
+        Some(self.old_text.clone()).filter(|t| !t.is_empty() && t != DEFAULT_TEXT)
+    }
+
     pub fn set(&mut self, entry: String) {
+        self.cursor = entry.chars().count();
         self.text = entry.clone();
         self.old_text = entry;
     }
 
-    pub fn render(&self, colored: bool) -> Paragraph {
-        let title = match self.changed {
-            true => "Repository*",
-            false => "Repository",
+    /// the byte index of the cursor
+    fn cursor_index(&self) -> usize {
+        self.text
+            .char_indices()
+            .nth(self.cursor)
+            .map(|(i, _)| i)
+            .unwrap_or(self.text.len())
+    }
+
+    pub fn render(&self, colored: bool) -> Paragraph<'_> {
+        let mut title = match self.changed {
+            true => format!("{}*", self.title),
+            false => String::from(self.title),
         };
+        if let Some((namespace, _)) = self
+            .text
+            .split_once('/')
+            .filter(|_| !self.default_text && self.title == REPO_TITLE)
+        {
+            title = format!("{} (namespace: {})", title, namespace);
+        }
 
         let border_style = if colored {
             Style::default().fg(Color::Green)
@@ -42,7 +88,26 @@ impl RepoEntry {
             Style::default().fg(Color::Gray)
         };
 
-        Paragraph::new(self.text.clone())
+        // the cursor is only shown while editing
+        let text = match colored {
+            false => Spans::from(self.text.clone()),
+            true => {
+                let index = self.cursor_index();
+                let (before, after) = self.text.split_at(index);
+                let mut chars = after.chars();
+                let under_cursor = chars.next().map(String::from).unwrap_or_else(|| " ".into());
+                Spans::from(vec![
+                    Span::raw(before.to_string()),
+                    Span::styled(
+                        under_cursor,
+                        Style::default().add_modifier(Modifier::REVERSED),
+                    ),
+                    Span::raw(chars.as_str().to_string()),
+                ])
+            }
+        };
+
+        Paragraph::new(text)
             .block(
                 Block::default()
                     .title(title)
@@ -57,29 +122,100 @@ impl RepoEntry {
         match key {
             // Key::Char('\n') => self.confirm(), //handled in Ui
             Key::Char(c) => {
-                self.text.push(c);
+                let index = self.cursor_index();
+                self.text.insert(index, c);
+                self.cursor += 1;
                 self.changed = true;
                 self.default_text = false;
             }
             Key::Backspace => {
                 if self.default_text {
                     self.text = String::new();
-                } else {
-                    self.text.pop();
+                    self.cursor = 0;
+                } else if self.cursor > 0 {
+                    self.cursor -= 1;
+                    let index = self.cursor_index();
+                    self.text.remove(index);
                 }
                 self.changed = true;
             }
+            Key::Delete if self.cursor < self.text.chars().count() => {
+                let index = self.cursor_index();
+                self.text.remove(index);
+                self.changed = true;
+            }
+            Key::Left => self.cursor = self.cursor.saturating_sub(1),
+            Key::Right => self.cursor = (self.cursor + 1).min(self.text.chars().count()),
+            Key::Home => self.cursor = 0,
+            Key::End => self.cursor = self.text.chars().count(),
             Key::Esc => {
                 self.text = self.old_text.clone();
+                self.cursor = self.text.chars().count();
                 self.changed = false;
             }
             _ => (),
         }
     }
 
-    /// set the widget to unchanged
+    /// set the widget to unchanged, a pasted url of a web page becomes its repository
     pub fn confirm(&mut self) {
+        self.text = crate::repo::from_web_url(&self.text);
+        self.cursor = self.cursor.min(self.text.chars().count());
         self.old_text = self.text.clone();
         self.changed = false;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use termion::event::Key;
+
+    use super::RepoEntry;
+
+    #[test]
+    fn test_edit_at_cursor() {
+        let mut entry = RepoEntry::new(Some("library/ngnx"));
+        let keys = vec![
+            Key::Left,
+            Key::Left,
+            Key::Char('i'),
+            Key::Home,
+            Key::Delete,
+            Key::Char('L'),
+            Key::End,
+            Key::Backspace,
+            Key::Char('x'),
+        ];
+        for key in keys {
+            entry.handle_input(key);
+        }
+        assert_eq!(entry.get(), "Library/nginx");
+
+        entry.handle_input(Key::Esc);
+        assert_eq!(entry.get(), "library/ngnx");
+    }
+
+    #[test]
+    fn test_get_confirmed() {
+        let mut entry = RepoEntry::new(None);
+        assert_eq!(entry.get_confirmed(), None);
+        entry.handle_input(Key::Backspace);
+        entry.handle_input(Key::Char('n'));
+        assert_eq!(entry.get_confirmed(), None);
+
+        entry.set("library/nginx".into());
+        entry.handle_input(Key::Char('x'));
+        assert_eq!(entry.get_confirmed().as_deref(), Some("library/nginx"));
+        entry.confirm();
+        assert_eq!(entry.get_confirmed().as_deref(), Some("library/nginxx"));
+    }
+
+    #[test]
+    fn test_prompt() {
+        let mut entry = RepoEntry::prompt("Save as", "compose.yml");
+        for key in [Key::Home, Key::Char('.'), Key::End, Key::Backspace] {
+            entry.handle_input(key);
+        }
+        assert_eq!(entry.get(), ".compose.ym");
+    }
+}
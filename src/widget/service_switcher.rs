@@ -1,13 +1,17 @@
+use std::collections::HashMap;
 use std::fmt;
 use std::fs::File;
 use std::io::BufRead;
 use std::io::BufReader;
 use std::io::Write;
-use std::path::PathBuf;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 
 use tui::style::{Color, Style};
 use tui::widgets::{Block, Borders, List, ListState};
 
+use crate::common::display_duration_ext::DisplayDurationExt;
+use crate::common::docker_daemon::Container;
 use crate::repo;
 
 #[derive(Debug)]
@@ -27,64 +31,146 @@ impl fmt::Display for Error {
 
 pub struct ServiceSwitcher {
     list: Vec<String>,
+    /// the lines as they were when the file was opened
+    original: Vec<String>,
+    /// the lines as they are in the file
+    saved: Vec<String>,
     state: ListState,
-    changed: bool,
     opened_file: PathBuf,
+    /// the files merged over the opened one, like further --file or the override file,
+    /// with the index of the line separating their lines from the ones before
+    merged: Vec<(PathBuf, usize)>,
+    /// containers running on the docker daemon
+    running: Vec<Container>,
+    /// age of the tag of lines which are considered stale
+    stale: HashMap<usize, chrono::Duration>,
+    /// repositories or services which are deliberately kept at their tag
+    pinned: Vec<String>,
+    /// image lines whose tags are compared with each other
+    marked: Vec<usize>,
 }
 
 impl ServiceSwitcher {
-    pub fn new(file: &Option<PathBuf>) -> Option<Self> {
+    /// opens the given files, merged in their order like compose does,
+    /// without files the first compose file of the current folder is opened
+    pub fn new(files: &[PathBuf], with_override: bool) -> Option<Self> {
+        let readable: Vec<(PathBuf, Vec<String>)> = files
+            .iter()
+            .filter_map(|file| read_lines(file).map(|lines| (file.clone(), lines)))
+            .collect();
+        if !readable.is_empty() {
+            return Some(Self::with_files(readable, with_override));
+        }
+
         //gather possible filenames
-        let mut file_list = vec![
-            PathBuf::from("docker-compose.yml"),
-            PathBuf::from("docker-compose.yaml"),
-        ];
-        match &file {
-            None => (),
-            Some(file) => file_list.insert(0, file.clone()),
+        let mut file_list = compose_files(Path::new(""));
+        if files.is_empty() {
+            file_list.append(&mut quadlet_files(Path::new(".")));
         }
 
         //try filenames
-        for file in file_list {
-            let list = match File::open(&file) {
-                Err(_) => continue,
-                Ok(file) => {
-                    let buf = BufReader::new(file);
-                    buf.lines()
-                        .map(|l| l.expect("Could not parse line"))
-                        .collect()
-                }
-            };
+        let found = file_list
+            .into_iter()
+            .find_map(|file| read_lines(&file).map(|lines| (file, lines)))?;
+        Some(Self::with_files(vec![found], with_override))
+    }
 
-            return Some(Self {
-                list,
-                state: ListState::default(),
-                changed: false,
-                opened_file: file,
-            });
+    /// the lines of all files one after another, each file after the first starts with a separator line
+    fn with_files(files: Vec<(PathBuf, Vec<String>)>, with_override: bool) -> Self {
+        let mut files = files.into_iter();
+        let (opened_file, mut list) = files.next().expect("at least one file");
+        let mut merged = vec![];
+        let mut append = |list: &mut Vec<String>, path: PathBuf, mut lines: Vec<String>| {
+            merged.push((path.clone(), list.len()));
+            list.push(format!("# ---- {} ----", path.display()));
+            list.append(&mut lines);
+        };
+
+        // the override file of each file follows its lines
+        let overrides = |file: &Path| match with_override {
+            true => read_lines(&override_path(file)).map(|lines| (override_path(file), lines)),
+            false => None,
+        };
+        if let Some((path, lines)) = overrides(&opened_file) {
+            append(&mut list, path, lines);
+        }
+        for (file, lines) in files {
+            let override_file = overrides(&file);
+            append(&mut list, file, lines);
+            if let Some((path, lines)) = override_file {
+                append(&mut list, path, lines);
+            }
         }
 
-        //could not find docker-compose file
-        None
+        Self {
+            original: list.clone(),
+            saved: list.clone(),
+            list,
+            state: ListState::default(),
+            opened_file,
+            merged,
+            running: vec![],
+            stale: HashMap::new(),
+            pinned: vec![],
+            marked: vec![],
+        }
     }
 
-    pub fn render(&mut self, colored: bool) -> (List, &mut ListState) {
+    pub fn render(&mut self, colored: bool) -> (List<'_>, &mut ListState) {
         let border_style = if colored {
             Style::default().fg(Color::Green)
         } else {
             Style::default().fg(Color::Gray)
         };
 
-        let title = match &self.changed {
-            true => format!("File: *{}*", &self.opened_file.display()),
-            false => format!("File: {}", &self.opened_file.display()),
+        let files: Vec<String> = self
+            .file_ranges()
+            .iter()
+            .map(|(path, _)| path.display().to_string())
+            .collect();
+        let files = files.join(" + ");
+        let title = match self.has_changes() {
+            true => format!("File: *{}*", files),
+            false => format!("File: {}", files),
         };
 
         let items: Vec<tui::widgets::ListItem> = self
             .list
             .iter()
-            .map(|l| {
-                tui::widgets::ListItem::new(l.as_ref())
+            .enumerate()
+            .map(|(i, l)| {
+                let line = match self.running_image(i) {
+                    None => l.clone(),
+                    Some(image) if repo::match_yaml_image(l).map(|(_, r)| r) == Ok(image) => {
+                        format!("{}   [running]", l)
+                    }
+                    Some(image) => format!("{}   [running {}]", l, image),
+                };
+                let line = match self.stale.get(&i) {
+                    None => line,
+                    Some(age) => format!("{}   ⚠ stale, {}", line, age.old()),
+                };
+                let line = match self.is_pinned(i) {
+                    true => format!("{}   [pinned]", line),
+                    false => line,
+                };
+                let line = match self.marked.contains(&i) {
+                    true => format!("{}   [marked]", line),
+                    false => line,
+                };
+                let line = match self.overriding_line(i) {
+                    None => line,
+                    Some(_) => format!("{}   [overridden]", line),
+                };
+                // with several files, each image shows the file it is written to
+                let line = match self.file_of(i) {
+                    Some(path) if !self.merged.is_empty() && repo_of(l).is_some() => {
+                        let name = path.file_name().unwrap_or_default().to_string_lossy();
+                        format!("{}   [{}]", line, name)
+                    }
+                    _ => line,
+                };
+                tui::widgets::ListItem::new(line)
                     .style(Style::default().fg(Color::White).bg(Color::Black))
             })
             .collect();
@@ -104,6 +190,132 @@ impl ServiceSwitcher {
         (items, &mut self.state)
     }
 
+    /// all lines with an image and their repo with tag
+    pub fn get_image_lines(&self) -> Vec<(usize, String)> {
+        self.list
+            .iter()
+            .enumerate()
+            .filter_map(|(i, l)| match repo::match_yaml_image(l) {
+                Ok((_, image)) => Some((i, image.to_string())),
+                Err(_) => None,
+            })
+            .collect()
+    }
+
+    /// the repositories in the file with the same name as the one of the selected line,
+    /// e.g. nginx, bitnami/nginx and ghcr.io/owner/nginx
+    pub fn related_repos(&self) -> Vec<String> {
+        let name = |repo: &str| repo.rsplit('/').next().unwrap_or(repo).to_string();
+        let current = match self.state.selected().and_then(|i| repo_of(&self.list[i])) {
+            Some(current) => name(current),
+            None => return vec![],
+        };
+        let mut repos: Vec<String> = vec![];
+        for repo in self.list.iter().filter_map(|l| repo_of(l)) {
+            if name(repo) == current && !repos.iter().any(|r| r == repo) {
+                repos.push(repo.to_string());
+            }
+        }
+        repos
+    }
+
+    /// marks the selected image line or removes its mark, returns its repo and whether it is marked now
+    pub fn toggle_mark(&mut self) -> Option<(String, bool)> {
+        let i = self.state.selected()?;
+        let repo = repo_of(&self.list[i])?.to_string();
+        match self.marked.iter().position(|m| *m == i) {
+            Some(position) => {
+                self.marked.remove(position);
+                Some((repo, false))
+            }
+            None => {
+                self.marked.push(i);
+                Some((repo, true))
+            }
+        }
+    }
+
+    /// the repositories of the marked lines in the order they were marked, without duplicates
+    pub fn marked_repos(&self) -> Vec<String> {
+        let mut repos: Vec<String> = vec![];
+        for repo in self.marked.iter().filter_map(|i| repo_of(&self.list[*i])) {
+            if !repos.iter().any(|r| r == repo) {
+                repos.push(repo.to_string());
+            }
+        }
+        repos
+    }
+
+    /// mark the line as stale, because its tag was not updated for the given time
+    pub fn set_stale(&mut self, line: usize, age: chrono::Duration) {
+        self.stale.insert(line, age);
+    }
+
+    /// the repositories or services which are not updated together with other lines
+    pub fn set_pinned(&mut self, pinned: Vec<String>) {
+        self.pinned = pinned;
+    }
+
+    /// whether the repository or the service of the line is pinned
+    pub fn is_pinned(&self, i: usize) -> bool {
+        let repo = match self.list.get(i).and_then(|l| repo_of(l)) {
+            Some(repo) => repo,
+            None => return false,
+        };
+        let service = self.service_name(i);
+        self.pinned.iter().any(|p| {
+            p == repo || p.strip_prefix("library/") == Some(repo) || Some(p.as_str()) == service
+        })
+    }
+
+    /// remember the running containers to show them next to the services
+    pub fn set_running(&mut self, containers: Vec<Container>) {
+        self.running = containers;
+    }
+
+    /// the image a container of the service in this line is running with
+    fn running_image(&self, i: usize) -> Option<&str> {
+        let (_, image) = repo::match_yaml_image(&self.list[i]).ok()?;
+        let service = self.service_name(i);
+
+        let container = match self
+            .running
+            .iter()
+            .find(|c| service.is_some() && c.service.as_deref() == service)
+        {
+            Some(container) => container,
+            None => {
+                let (name, _) = repo::split_tag_from_repo(image).ok()?;
+                self.running.iter().find(|c| {
+                    c.service.is_none()
+                        && repo::split_tag_from_repo(&c.image).map(|(r, _)| r) == Ok(name)
+                })?
+            }
+        };
+        Some(&container.image)
+    }
+
+    /// the name of the service the line belongs to, which is the nearest key with less indentation,
+    /// the service of a quadlet is named by its file
+    pub fn service_name(&self, i: usize) -> Option<&str> {
+        let (file, lines) = self.range_of(i)?;
+        if is_quadlet(file) {
+            return file.file_stem()?.to_str();
+        }
+        lazy_static::lazy_static! {
+            static ref REGEX: regex::Regex = regex::Regex::new(r"^( *)([A-Za-z0-9._\-]+) *: *$").unwrap();
+        }
+        let indentation = |l: &str| l.len() - l.trim_start_matches(' ').len();
+        let line_indentation = indentation(self.list.get(i)?);
+
+        self.list[lines.start..i]
+            .iter()
+            .rev()
+            .filter_map(|l| REGEX.captures(l))
+            .find(|caps| caps.get(1).unwrap().as_str().len() < line_indentation)
+            .map(|caps| caps.get(2).unwrap().as_str())
+    }
+
     /// finds the next image tag in given file
     pub fn find_next_match(&mut self) -> bool {
         let current_line: usize = self.state.selected().unwrap_or(0);
@@ -128,6 +340,29 @@ impl ServiceSwitcher {
         false
     }
 
+    /// selects the first image of all files
+    pub fn find_first_match(&mut self) -> bool {
+        let first = (0..self.list.len()).find(|&i| repo::match_yaml_image(&self.list[i]).is_ok());
+        self.select_if_other(first)
+    }
+
+    /// selects the last image of all files
+    pub fn find_last_match(&mut self) -> bool {
+        let last = (0..self.list.len())
+            .rev()
+            .find(|&i| repo::match_yaml_image(&self.list[i]).is_ok());
+        self.select_if_other(last)
+    }
+
+    /// whether the line was selected, because it is another one than the selected line
+    fn select_if_other(&mut self, line: Option<usize>) -> bool {
+        if line.is_none() || line == self.state.selected() {
+            return false;
+        }
+        self.state.select(line);
+        true
+    }
+
     /// finds the previous image tag in given file
     pub fn find_previous_match(&mut self) -> bool {
         let current_line: usize = self.state.selected().unwrap_or(0);
@@ -158,6 +393,49 @@ impl ServiceSwitcher {
         false
     }
 
+    /// the file the selected line is in
+    pub fn current_file(&self) -> &Path {
+        let selected = self.state.selected().unwrap_or(0);
+        self.file_of(selected).unwrap_or(&self.opened_file)
+    }
+
+    /// the file the line is written to, none for the separators between the files
+    fn file_of(&self, i: usize) -> Option<&Path> {
+        self.range_of(i).map(|(path, _)| path)
+    }
+
+    /// the file of the line and all lines of it
+    fn range_of(&self, i: usize) -> Option<(&Path, Range<usize>)> {
+        self.file_ranges()
+            .into_iter()
+            .find(|(_, lines)| lines.contains(&i))
+    }
+
+    pub fn selected_line(&self) -> Option<usize> {
+        self.state.selected()
+    }
+
+    /// the service of the selected line
+    pub fn current_service(&self) -> Option<String> {
+        self.service_name(self.state.selected()?).map(String::from)
+    }
+
+    /// whether any line differs from the files
+    pub fn has_changes(&self) -> bool {
+        self.files().iter().any(|(_, changed)| *changed)
+    }
+
+    /// select the image line of the service, returns whether it has one
+    pub fn select_service(&mut self, service: &str) -> bool {
+        let line = (0..self.list.len()).find(|&i| {
+            repo::match_yaml_image(&self.list[i]).is_ok() && self.service_name(i) == Some(service)
+        });
+        if line.is_some() {
+            self.state.select(line);
+        }
+        line.is_some()
+    }
+
     /// return the repository from currently selected row
     pub fn extract_repo(&self) -> Result<String, Error> {
         match self.state.selected() {
@@ -169,27 +447,663 @@ impl ServiceSwitcher {
         }
     }
 
-    /// replace currently selected line with repo and tag
-    pub fn change_current_line(&mut self, repo_with_tag: String) {
+    /// the currently selected line as it is in the file
+    pub fn get_current_line(&self) -> Result<&str, Error> {
         match self.state.selected() {
-            None => (),
-            Some(i) => match repo::match_yaml_image(&self.list[i]) {
-                Err(_) => return,
-                Ok((front, _)) => self.list[i] = format!("{}{}", front, repo_with_tag),
-            },
+            None => Err(Error::NoneSelected),
+            Some(i) => Ok(&self.list[i]),
         }
-        self.changed = true;
     }
 
-    /// save the currently opened file
-    pub fn save(&mut self) -> Result<(), std::io::Error> {
-        let mut file = File::create(&self.opened_file)?;
-        for line in &self.list {
+    /// the line of a later file which defines the effective image of the service in this line,
+    /// the last file wins like in compose
+    fn overriding_line(&self, i: usize) -> Option<usize> {
+        let (_, lines) = self.range_of(i)?;
+        if repo::match_yaml_image(&self.list[i]).is_err() {
+            return None;
+        }
+        let service = self.service_name(i)?;
+        (lines.end..self.list.len()).rev().find(|&j| {
+            repo::match_yaml_image(&self.list[j]).is_ok() && self.service_name(j) == Some(service)
+        })
+    }
+
+    /// replace currently selected line with repo and tag,
+    /// an image which is overridden is changed where it is effective
+    pub fn change_current_line(&mut self, repo_with_tag: String) {
+        if let Some(i) = self.state.selected() {
+            let i = self.overriding_line(i).unwrap_or(i);
+            self.change_lines(&[i], &repo_with_tag);
+        }
+    }
+
+    /// all lines with an image of the same repository as the selected line
+    pub fn lines_with_current_repo(&self) -> Vec<usize> {
+        let current = match self.state.selected().and_then(|i| repo_of(&self.list[i])) {
+            Some(current) => current,
+            None => return vec![],
+        };
+
+        (0..self.list.len())
+            .filter(|&i| repo_of(&self.list[i]) == Some(current))
+            .collect()
+    }
+
+    /// replace the image of the given lines with repo and tag, returns how many lines were changed
+    pub fn change_lines(&mut self, lines: &[usize], repo_with_tag: &str) -> usize {
+        let mut changed = 0;
+        for &i in lines {
+            let front = match self.list.get(i).map(|l| repo::match_yaml_image(l)) {
+                Some(Ok((front, _))) => front.to_string(),
+                _ => continue,
+            };
+            self.list[i] = format!("{}{}", front, repo_with_tag);
+            self.stale.remove(&i);
+            changed += 1;
+        }
+        changed
+    }
+
+    /// put back the image of the selected line as it was when the file was opened,
+    /// an image which is overridden is reverted where it is effective
+    pub fn revert_current_line(&mut self) -> Result<&str, Error> {
+        let i = match self.state.selected() {
+            None => return Err(Error::NoneSelected),
+            Some(i) => self.overriding_line(i).unwrap_or(i),
+        };
+        let original = match self.original.get(i) {
+            Some(l) if repo::match_yaml_image(l).is_ok() => l.clone(),
+            _ => return Err(Error::Parsing(String::from("No image to revert to"))),
+        };
+
+        if self.list[i] != original {
+            self.list[i] = original;
+            self.stale.remove(&i);
+        }
+        Ok(&self.list[i])
+    }
+
+    /// the changed images since the last save, with their service
+    pub fn get_changes(&self) -> Vec<String> {
+        let image = |l: &str| {
+            repo::match_yaml_image(l)
+                .map(|(_, image)| image.to_string())
+                .unwrap_or_else(|_| l.trim().to_string())
+        };
+        (0..self.list.len())
+            .filter(|&i| self.saved.get(i) != Some(&self.list[i]))
+            .map(|i| {
+                let old = self.saved.get(i).map(|l| image(l)).unwrap_or_default();
+                format!(
+                    "{}: {} → {}",
+                    self.service_name(i).unwrap_or("?"),
+                    old,
+                    image(&self.list[i])
+                )
+            })
+            .collect()
+    }
+
+    /// the lines of each loaded file
+    fn file_ranges(&self) -> Vec<(&Path, Range<usize>)> {
+        let mut ranges = vec![];
+        let (mut path, mut start) = (self.opened_file.as_path(), 0);
+        for (next, separator) in &self.merged {
+            ranges.push((path, start..*separator));
+            (path, start) = (next.as_path(), separator + 1);
+        }
+        ranges.push((path, start..self.list.len()));
+        ranges
+    }
+
+    /// the loaded files and whether they have unsaved changes
+    pub fn files(&self) -> Vec<(String, bool)> {
+        self.file_ranges()
+            .into_iter()
+            .map(|(path, mut lines)| {
+                let changed = lines.any(|i| self.saved.get(i) != Some(&self.list[i]));
+                (path.display().to_string(), changed)
+            })
+            .collect()
+    }
+
+    /// select the first image of the file, returns whether there is one
+    pub fn select_file(&mut self, file: usize) -> bool {
+        let lines = match self.file_ranges().get(file) {
+            Some((_, lines)) => lines.clone(),
+            None => return false,
+        };
+        match lines
+            .clone()
+            .find(|&i| repo::match_yaml_image(&self.list[i]).is_ok())
+        {
+            Some(i) => {
+                self.state.select(Some(i));
+                true
+            }
+            None => {
+                self.state
+                    .select(Some(lines.start.min(self.list.len() - 1)));
+                false
+            }
+        }
+    }
+
+    /// save each loaded file with changes to where its lines came from,
+    /// with backup the files are copied first and the copies are returned
+    pub fn save(&mut self, backup: bool) -> Result<Vec<PathBuf>, std::io::Error> {
+        let time = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+        let mut backups = vec![];
+        for (path, lines) in self.file_ranges() {
+            if lines
+                .clone()
+                .all(|i| self.saved.get(i) == Some(&self.list[i]))
+            {
+                continue;
+            }
+            if backup && path.exists() {
+                let copy = backup_path(path, &time);
+                std::fs::copy(path, &copy)?;
+                backups.push(copy);
+            }
+            write_lines(path, &self.list[lines])?;
+        }
+
+        self.saved = self.list.clone();
+        Ok(backups)
+    }
+
+    /// writes the lines of the opened file to another file, which is created or replaced,
+    /// the opened file stays unchanged and unsaved
+    pub fn save_as(&self, path: &Path) -> Result<(), std::io::Error> {
+        let (_, lines) = self.file_ranges().remove(0);
+        write_lines(path, &self.list[lines])
+    }
+}
+
+fn read_lines(path: &Path) -> Option<Vec<String>> {
+    let file = File::open(path).ok()?;
+    let buf = BufReader::new(file);
+    Some(
+        buf.lines()
+            .map(|l| l.expect("Could not parse line"))
+            .collect(),
+    )
+}
+
+/// writes a temporary file next to the file and renames it over the file,
+/// so the file is never left half written
+fn write_lines(path: &Path, lines: &[String]) -> Result<(), std::io::Error> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temporary = path.with_file_name(format!(".{}.reel-moby.tmp", name));
+    let written = (|| {
+        let mut file = File::create(&temporary)?;
+        for line in lines {
             file.write_all(line.as_bytes())?;
             file.write_all("\n".as_bytes())?;
         }
+        file.sync_all()?;
+        if let Ok(metadata) = std::fs::metadata(path) {
+            std::fs::set_permissions(&temporary, metadata.permissions())?;
+        }
+        std::fs::rename(&temporary, path)
+    })();
+
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&temporary);
+        return Err(std::io::Error::new(
+            e.kind(),
+            format!("could not write {}: {}", path.display(), e),
+        ));
+    }
+    Ok(())
+}
+
+/// the compose files of the folder in the order docker compose looks for them
+fn compose_files(dir: &Path) -> Vec<PathBuf> {
+    const NAMES: [&str; 4] = [
+        "compose.yaml",
+        "compose.yml",
+        "docker-compose.yml",
+        "docker-compose.yaml",
+    ];
+    NAMES
+        .iter()
+        .map(|name| dir.join(name))
+        .filter(|p| p.is_file())
+        .collect()
+}
+
+/// podman quadlet files which define a container, e.g. nginx.container
+fn quadlet_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = match std::fs::read_dir(dir) {
+        Ok(read) => read
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| is_quadlet(p))
+            .collect(),
+        Err(_) => vec![],
+    };
+    files.sort();
+    files
+}
+
+fn is_quadlet(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some("container")
+}
+
+/// a copy next to the file, e.g. docker-compose.yml.20240301-120000.bak
+fn backup_path(file: &Path, time: &str) -> PathBuf {
+    let name = file.file_name().unwrap_or_default().to_string_lossy();
+    file.with_file_name(format!("{}.{}.bak", name, time))
+}
+
+/// the override file compose uses for a file, e.g. docker-compose.override.yml
+fn override_path(file: &Path) -> PathBuf {
+    let stem = file.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match file.extension() {
+        Some(extension) => format!("{}.override.{}", stem, extension.to_string_lossy()),
+        None => format!("{}.override", stem),
+    };
+    file.with_file_name(name)
+}
+
+/// the repository without tag of the image in a line
+fn repo_of(line: &str) -> Option<&str> {
+    let (_, image) = repo::match_yaml_image(line).ok()?;
+    repo::split_tag_from_repo(image).ok().map(|(r, _)| r)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+
+    use tui::widgets::ListState;
+
+    use super::ServiceSwitcher;
+
+    fn switcher(lines: &[&str]) -> ServiceSwitcher {
+        ServiceSwitcher {
+            list: lines.iter().map(|l| l.to_string()).collect(),
+            original: lines.iter().map(|l| l.to_string()).collect(),
+            saved: lines.iter().map(|l| l.to_string()).collect(),
+            state: ListState::default(),
+            opened_file: PathBuf::from("docker-compose.yml"),
+            merged: vec![],
+            running: vec![],
+            stale: HashMap::new(),
+            pinned: vec![],
+            marked: vec![],
+        }
+    }
+
+    #[test]
+    fn test_service_name() {
+        let services = switcher(&[
+            "version: '3'",
+            "services:",
+            "  web:",
+            "    image: nginx:1.25",
+            "    ports:",
+            "      - 80:80",
+            "  db:",
+            "    environment:",
+            "      KEY: value",
+            "    image: postgres:16",
+        ]);
+
+        assert_eq!(services.service_name(3), Some("web"));
+        assert_eq!(services.service_name(9), Some("db"));
+        assert_eq!(services.service_name(1), None);
+    }
+
+    #[test]
+    fn test_find_first_and_last_match() {
+        let mut services = switcher(&[
+            "services:",
+            "  web:",
+            "    image: nginx:1.25",
+            "  db:",
+            "    image: postgres:16",
+            "    ports:",
+        ]);
+
+        assert!(services.find_last_match());
+        assert_eq!(services.state.selected(), Some(4));
+        assert!(!services.find_last_match());
+        assert!(services.find_first_match());
+        assert_eq!(services.state.selected(), Some(2));
+        assert!(!services.find_first_match());
+    }
+
+    #[test]
+    fn test_change_all_lines_of_repo() {
+        let mut services = switcher(&[
+            "services:",
+            "  web:",
+            "    image: nginx:1.25",
+            "  proxy:",
+            "    image: nginx:1.24",
+            "  db:",
+            "    image: postgres:16",
+        ]);
+        services.state.select(Some(2));
+
+        let lines = services.lines_with_current_repo();
+        assert_eq!(lines, vec![2, 4]);
+        assert_eq!(services.change_lines(&lines, "nginx:1.27"), 2);
+        assert_eq!(services.list[2], "    image: nginx:1.27");
+        assert_eq!(services.list[4], "    image: nginx:1.27");
+        assert_eq!(services.list[6], "    image: postgres:16");
+        assert!(services.has_changes());
+    }
+
+    #[test]
+    fn test_revert_current_line() {
+        let mut services = switcher(&["services:", "  web:", "    image: nginx:1.25"]);
+
+        services.state.select(Some(2));
+        services.change_current_line("nginx:1.27".into());
+        assert_eq!(services.list[2], "    image: nginx:1.27");
+        assert!(services.has_changes());
+        assert_eq!(
+            services.revert_current_line().unwrap(),
+            "    image: nginx:1.25"
+        );
+        assert!(!services.has_changes());
+
+        services.state.select(Some(1));
+        assert!(services.revert_current_line().is_err());
+    }
+
+    #[test]
+    fn test_get_changes() {
+        let mut services = switcher(&[
+            "services:",
+            "  web:",
+            "    image: nginx:1.25",
+            "  db:",
+            "    image: postgres:16",
+        ]);
+        assert!(services.get_changes().is_empty());
+
+        services.state.select(Some(4));
+        services.change_current_line("postgres:17".into());
+        assert_eq!(
+            services.get_changes(),
+            vec!["db: postgres:16 → postgres:17"]
+        );
+    }
+
+    #[test]
+    fn test_override_path() {
+        let input: Vec<(&str, &str)> = vec![
+            ("docker-compose.yml", "docker-compose.override.yml"),
+            ("deploy/compose.yaml", "deploy/compose.override.yaml"),
+        ];
+
+        for i in input {
+            assert_eq!(
+                super::override_path(&PathBuf::from(i.0)),
+                PathBuf::from(i.1)
+            );
+        }
+    }
+
+    #[test]
+    fn test_change_overridden_line() {
+        let mut services = switcher(&[
+            "services:",
+            "  web:",
+            "    image: nginx:1.25",
+            "  db:",
+            "    image: postgres:16",
+            "# ---- docker-compose.override.yml ----",
+            "services:",
+            "  web:",
+            "    image: nginx:1.26",
+        ]);
+        services.merged = vec![(PathBuf::from("docker-compose.override.yml"), 5)];
+
+        assert_eq!(services.service_name(8), Some("web"));
+        assert_eq!(services.overriding_line(2), Some(8));
+        assert_eq!(services.overriding_line(4), None);
+
+        services.state.select(Some(2));
+        services.change_current_line("nginx:1.27".into());
+        assert_eq!(services.list[2], "    image: nginx:1.25");
+        assert_eq!(services.list[8], "    image: nginx:1.27");
+
+        assert_eq!(
+            services.revert_current_line().unwrap(),
+            "    image: nginx:1.26"
+        );
+        assert_eq!(services.list[2], "    image: nginx:1.25");
+        assert_eq!(services.list[8], "    image: nginx:1.26");
+    }
+
+    #[test]
+    fn test_several_files() {
+        let dir = std::env::temp_dir().join("reel-moby-several-files-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let (base, more) = (dir.join("base.yml"), dir.join("more.yml"));
+        std::fs::write(&base, "services:\n  web:\n    image: nginx:1.25\n").unwrap();
+        std::fs::write(&more, "services:\n  db:\n    image: postgres:16\n").unwrap();
+
+        let mut services = ServiceSwitcher::new(&[base.clone(), more.clone()], false).unwrap();
+        assert_eq!(
+            services.files(),
+            vec![
+                (base.display().to_string(), false),
+                (more.display().to_string(), false)
+            ]
+        );
+        assert!(services.select_service("db"));
+        assert_eq!(services.current_file(), more.as_path());
+        services.change_current_line("postgres:16.2".into());
+        services.save(false).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&base).unwrap(),
+            "services:\n  web:\n    image: nginx:1.25\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(&more).unwrap(),
+            "services:\n  db:\n    image: postgres:16.2\n"
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_save_as() {
+        let dir = std::env::temp_dir().join("reel-moby-save-as-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("compose.yml");
+        std::fs::write(&path, "services:\n  web:\n    image: nginx:1.25\n").unwrap();
+        let mut services = ServiceSwitcher::new(std::slice::from_ref(&path), false).unwrap();
+        services.select_service("web");
+        services.change_current_line("nginx:1.27".into());
+
+        let copy = dir.join("preview.yml");
+        services.save_as(&copy).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&copy).unwrap(),
+            "services:\n  web:\n    image: nginx:1.27\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "services:\n  web:\n    image: nginx:1.25\n"
+        );
+        assert!(services.has_changes());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_lines() {
+        let dir = std::env::temp_dir().join("reel-moby-write-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("compose.yml");
+        std::fs::write(&path, "services:\n").unwrap();
+
+        let lines = vec!["services:".to_string(), "  web:".to_string()];
+        super::write_lines(&path, &lines).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "services:\n  web:\n"
+        );
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        let missing = dir.join("missing").join("compose.yml");
+        assert!(super::write_lines(&missing, &lines)
+            .unwrap_err()
+            .to_string()
+            .starts_with("could not write"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_backup_path() {
+        assert_eq!(
+            super::backup_path(Path::new("compose/docker-compose.yml"), "20240301-120000"),
+            PathBuf::from("compose/docker-compose.yml.20240301-120000.bak")
+        );
+    }
+
+    #[test]
+    fn test_compose_files() {
+        let dir = std::env::temp_dir().join("reel-moby-compose-files-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["docker-compose.yml", "compose.yaml", "other.yml"] {
+            std::fs::write(dir.join(name), "services:\n").unwrap();
+        }
+
+        assert_eq!(
+            super::compose_files(&dir),
+            vec![dir.join("compose.yaml"), dir.join("docker-compose.yml")]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_related_repos() {
+        let mut services = switcher(&[
+            "services:",
+            "  web:",
+            "    image: nginx:1.25",
+            "  proxy:",
+            "    image: bitnami/nginx:1.24",
+            "  edge:",
+            "    image: nginx:1.26",
+            "  db:",
+            "    image: postgres:16",
+        ]);
+        services.state.select(Some(4));
+        assert_eq!(services.related_repos(), vec!["nginx", "bitnami/nginx"]);
+
+        services.state.select(Some(8));
+        assert_eq!(services.related_repos(), vec!["postgres"]);
+
+        services.state.select(Some(0));
+        assert!(services.related_repos().is_empty());
+    }
+
+    #[test]
+    fn test_marked_repos() {
+        let mut services = switcher(&[
+            "services:",
+            "  web:",
+            "    image: nginx:1.25",
+            "  cache:",
+            "    image: redis:7",
+            "  edge:",
+            "    image: nginx:1.26",
+        ]);
+        services.state.select(Some(0));
+        assert_eq!(services.toggle_mark(), None);
+
+        for i in [4, 2, 6] {
+            services.state.select(Some(i));
+            assert!(services.toggle_mark().unwrap().1);
+        }
+        assert_eq!(services.marked_repos(), vec!["redis", "nginx"]);
+
+        services.state.select(Some(4));
+        assert_eq!(services.toggle_mark(), Some((String::from("redis"), false)));
+        assert_eq!(services.marked_repos(), vec!["nginx"]);
+    }
+
+    #[test]
+    fn test_is_pinned() {
+        let mut services = switcher(&[
+            "services:",
+            "  web:",
+            "    image: nginx:1.25",
+            "  db:",
+            "    image: postgres:16",
+            "  cache:",
+            "    image: redis:7",
+        ]);
+        services.set_pinned(vec!["library/postgres".into(), "cache".into()]);
+        let input: Vec<(usize, bool)> = vec![(0, false), (2, false), (4, true), (6, true)];
+
+        for i in input {
+            assert_eq!(services.is_pinned(i.0), i.1, "{}", i.0);
+        }
+    }
+
+    #[test]
+    fn test_quadlet() {
+        let mut services = switcher(&[
+            "[Unit]",
+            "Description=web server",
+            "",
+            "[Container]",
+            "Image=docker.io/library/nginx:1.25",
+            "PublishPort=8080:80",
+        ]);
+        services.opened_file = PathBuf::from("nginx.container");
+
+        assert!(services.find_next_match());
+        assert_eq!(services.state.selected(), Some(4));
+        assert_eq!(services.service_name(4), Some("nginx"));
+        assert_eq!(
+            services.extract_repo().unwrap(),
+            "docker.io/library/nginx:1.25"
+        );
+
+        services.change_current_line("docker.io/library/nginx:1.26".into());
+        assert_eq!(services.list[4], "Image=docker.io/library/nginx:1.26");
+        assert_eq!(services.list[5], "PublishPort=8080:80");
+    }
+
+    #[test]
+    fn test_files() {
+        let mut services = switcher(&[
+            "services:",
+            "  web:",
+            "    image: nginx:1.25",
+            "# ---- docker-compose.override.yml ----",
+            "services:",
+            "  web:",
+            "    image: nginx:1.26",
+        ]);
+        services.merged = vec![(PathBuf::from("docker-compose.override.yml"), 3)];
+
+        services.state.select(Some(6));
+        services.change_current_line("nginx:1.27".into());
+        assert_eq!(
+            services.files(),
+            vec![
+                ("docker-compose.yml".to_string(), false),
+                ("docker-compose.override.yml".to_string(), true)
+            ]
+        );
 
-        self.changed = false;
-        Ok(())
+        assert!(services.select_file(0));
+        assert_eq!(services.state.selected(), Some(2));
+        assert!(services.select_file(1));
+        assert_eq!(services.state.selected(), Some(6));
+        assert!(!services.select_file(2));
     }
 }
@@ -1,15 +1,25 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::sync::mpsc;
+use std::thread;
 
+use chrono::{DateTime, Duration, Utc};
 use termion::event::Key;
-use tui::style::{Color, Style};
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
 use tui::widgets::{Block, Borders, List, ListState};
 
+use crate::common::version;
 use crate::repository;
+use crate::widget::details::Platform;
 
 pub enum Error {
     NoneSelected,
     NextPageSelected,
     SelectedStatus,
+    /// the typed filter matches more than one tag
+    AmbiguousMatch(usize),
 }
 
 impl fmt::Display for Error {
@@ -18,6 +28,7 @@ impl fmt::Display for Error {
             Error::NoneSelected => write!(f, "No tag selected"),
             Error::NextPageSelected => write!(f, "tried to get the next page"),
             Error::SelectedStatus => write!(f, "Status message was selected"),
+            Error::AmbiguousMatch(n) => write!(f, "{} tags match, type more to narrow down", n),
         }
     }
 }
@@ -38,10 +49,185 @@ impl fmt::Display for Line {
     }
 }
 
+/// presets in days to cycle through when filtering interactively
+const SINCE_PRESETS: [i64; 4] = [7, 30, 90, 365];
+
+/// operating systems to cycle through when filtering interactively
+const OS_PRESETS: [&str; 2] = ["linux", "windows"];
+
+/// a tag of a repo and when it was updated, e.g. the one in the compose file
+#[derive(Clone, Debug, PartialEq)]
+pub struct NewerThan {
+    pub repo: String,
+    pub tag: String,
+    pub updated: DateTime<Utc>,
+}
+
+/// settings which hide tags from the list
+#[derive(Clone, Default)]
+pub struct Filter {
+    /// only show tags which were updated after this point in time
+    pub since: Option<DateTime<Utc>>,
+    since_preset: Option<usize>,
+    /// only show the newest tag of each variant family
+    pub newest_per_variant: bool,
+    /// patterns which name variant families, e.g. alpine
+    variants: Vec<regex::Regex>,
+    /// only show tags with an image for this os
+    pub os: Option<&'static str>,
+    /// only show tags updated after this tag, the tags of other repos are all shown
+    pub newer_than: Option<NewerThan>,
+    /// only show tags with an image for this platform, e.g. linux/arm64
+    platform: Option<Platform>,
+}
+
+impl Filter {
+    pub fn new(since: Option<DateTime<Utc>>) -> Self {
+        Self {
+            since,
+            since_preset: None,
+            newest_per_variant: false,
+            variants: vec![],
+            os: None,
+            newer_than: None,
+            platform: None,
+        }
+    }
+
+    /// hide the tags without an image for the platform
+    pub fn with_platform(self, platform: Option<Platform>) -> Self {
+        Self { platform, ..self }
+    }
+
+    /// name variant families by these patterns instead of the suffix of the tags, invalid ones are skipped
+    pub fn with_variants(mut self, patterns: &[String]) -> Self {
+        self.variants = patterns
+            .iter()
+            .filter_map(|p| regex::Regex::new(p).ok())
+            .collect();
+        self
+    }
+
+    /// show only the newest tag of each variant or all tags and describe it
+    pub fn toggle_newest_per_variant(&mut self) -> &'static str {
+        self.newest_per_variant = !self.newest_per_variant;
+        match self.newest_per_variant {
+            true => "Showing the newest tag of each variant",
+            false => "Showing the tags of all variants",
+        }
+    }
+
+    /// show only tags updated after the given one or all tags again and describe it
+    pub fn set_newer_than(&mut self, tag: Option<NewerThan>) -> String {
+        self.newer_than = tag;
+        match &self.newer_than {
+            Some(newer_than) => format!("Showing tags updated after {}", newer_than.tag),
+            None => String::from("Showing tags older and newer than the current one"),
+        }
+    }
+
+    /// check if the tag of the repo should be shown, tags without a date, os or platforms are kept
+    fn matches(&self, tag: &repository::Tag, repo: Option<&str>) -> bool {
+        let since = match (self.since, tag.last_updated()) {
+            (Some(since), Some(updated)) => updated >= since,
+            _ => true,
+        };
+        let newer = match (&self.newer_than, tag.last_updated()) {
+            (Some(newer_than), Some(updated)) if repo == Some(newer_than.repo.as_str()) => {
+                updated > newer_than.updated
+            }
+            _ => true,
+        };
+        let os = match self.os {
+            Some(os) => {
+                let systems = operating_systems(tag);
+                systems.is_empty() || systems.contains(&os)
+            }
+            None => true,
+        };
+        let platform = match self.platform {
+            Some(platform) => {
+                let details = tag.get_details();
+                details.is_empty() || details.iter().any(|d| platform.matches(d))
+            }
+            None => true,
+        };
+        since && newer && os && platform
+    }
+
+    /// switch to the next os to filter by and describe it
+    pub fn cycle_os(&mut self) -> String {
+        self.os = match self.os {
+            None => Some(OS_PRESETS[0]),
+            Some(os) => OS_PRESETS.iter().skip_while(|&&p| p != os).nth(1).copied(),
+        };
+        match self.os {
+            None => String::from("Showing tags of any os"),
+            Some(os) => format!("Showing only tags with a {} image", os),
+        }
+    }
+
+    /// switch to the next preset for the since filter and describe it
+    pub fn cycle_since(&mut self) -> String {
+        self.since_preset = match self.since_preset {
+            None => Some(0),
+            Some(i) if i + 1 < SINCE_PRESETS.len() => Some(i + 1),
+            Some(_) => None,
+        };
+
+        match self.since_preset {
+            None => {
+                self.since = None;
+                String::from("Showing tags of any age")
+            }
+            Some(i) => {
+                self.since = Some(Utc::now() - Duration::days(SINCE_PRESETS[i]));
+                format!("Showing tags updated in the last {} days", SINCE_PRESETS[i])
+            }
+        }
+    }
+}
+
+/// how the lines of the list are shown
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ListView {
+    /// number the lines and don't rely on colors
+    pub accessible: bool,
+    /// show how many platforms each tag has
+    pub platform_count: bool,
+}
+
 pub struct TagList {
     lines: Vec<Line>,
+    /// indices of lines which pass the filter
+    visible: Vec<usize>,
     state: ListState,
     tags: Option<repository::Repo>,
+    filter: Filter,
+    /// typed text a tag name has to contain
+    search: String,
+    /// compare the search with the exact case of the tag names
+    case_sensitive: bool,
+    /// names of tags marked for an action
+    marked: HashSet<String>,
+    /// show tags with the same digest as one line
+    grouped: bool,
+    /// sort the tags by version instead of the order of the registry
+    by_version: bool,
+    /// digests of groups which show all their tags
+    expanded: HashSet<String>,
+    /// the url the first page of tags was requested from
+    url: Option<String>,
+    /// the name of the repository the tags belong to
+    repo: Option<String>,
+    /// names of tags whose digest changed since the last visit
+    moved: HashSet<String>,
+    /// how many of the tags were compared with the last visit
+    checked: usize,
+    /// the frame of the spinner in the title while tags are fetched
+    spinner: Option<char>,
+    /// the next page while it is fetched in the background
+    next_page: Option<mpsc::Receiver<Result<repository::Repo, repository::Error>>>,
 }
 
 impl TagList {
@@ -49,43 +235,328 @@ impl TagList {
     pub fn with_status(status: &str) -> Self {
         Self {
             lines: vec![Line::Status(String::from(status))],
+            visible: vec![0],
             state: ListState::default(),
             tags: None,
+            filter: Filter::default(),
+            search: String::new(),
+            case_sensitive: false,
+            marked: HashSet::new(),
+            grouped: false,
+            by_version: false,
+            expanded: HashSet::new(),
+            url: None,
+            repo: None,
+            moved: HashSet::new(),
+            checked: 0,
+            spinner: None,
+            next_page: None,
         }
     }
 
-    /// list the tags of the repository if the input is valid
-    pub fn with_repo_name(repo: String) -> Self {
-        match repository::Repo::new(&repo) {
-            Ok(tags) => Self::with_tags(tags),
-            Err(_) => Self::with_status("input repo was not found"),
+    /// list the fetched tags of the repository, or why they could not be fetched
+    pub fn with_fetched(
+        repo: String,
+        fetched: Result<repository::Repo, repository::Error>,
+        filter: &Filter,
+    ) -> Self {
+        match fetched {
+            Ok(tags) => {
+                let mut list = Self::with_tags(tags, filter);
+                list.repo = Some(repo);
+                list
+            }
+            Err(e) => Self::with_status(&format!("{}", e)),
         }
     }
 
     /// list the tags of the input
-    fn with_tags(mut tags: repository::Repo) -> Self {
+    pub fn with_tags(tags: repository::Repo, filter: &Filter) -> Self {
+        let url = tags.get_url();
         let mut lines: Vec<Line> = tags
             .get_tags()
             .iter()
             .map(|r| Line::Image(r.clone()))
             .collect();
 
-        match tags.next_page() {
-            None => (),
-            Some(new_tags) => {
-                lines.push(Line::NextPage(String::from("load more tags")));
-                tags = new_tags;
-            }
-        };
+        if tags.has_next_page() {
+            lines.push(Line::NextPage(String::from("load more tags")));
+        }
 
-        Self {
+        let mut list = Self {
             lines,
+            visible: vec![],
             state: ListState::default(),
             tags: Some(tags),
+            filter: filter.clone(),
+            search: String::new(),
+            case_sensitive: false,
+            marked: HashSet::new(),
+            grouped: false,
+            by_version: false,
+            expanded: HashSet::new(),
+            url,
+            repo: None,
+            moved: HashSet::new(),
+            checked: 0,
+            spinner: None,
+            next_page: None,
+        };
+        list.update_visible();
+        list
+    }
+
+    /// the url the tags were requested from, without any secrets
+    pub fn get_url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    /// compare the search with the exact case of the tag names or ignore the case and describe it
+    pub fn toggle_case_sensitive(&mut self) -> &'static str {
+        self.case_sensitive = !self.case_sensitive;
+        self.update_visible();
+        match self.case_sensitive {
+            true => "The filter is case sensitive",
+            false => "The filter ignores the case",
+        }
+    }
+
+    /// whether a text to filter the tags was typed
+    pub fn is_searching(&self) -> bool {
+        !self.search.is_empty()
+    }
+
+    /// select the tag whose last number is the one of the selected tag changed by delta
+    pub fn select_bumped(&mut self, delta: i64) -> String {
+        let current = match self.get_selected_name() {
+            Some(current) => current,
+            None => return String::from("No tag selected"),
+        };
+        let bumped = match version::bump(&current, delta) {
+            Some(bumped) => bumped,
+            None => return format!("{} has no number to change", current),
+        };
+        if !self.get_tags().iter().any(|t| t.get_name() == bumped) {
+            return format!("{} is not in the loaded tags", bumped);
+        }
+        self.select_tag(&bumped);
+        match self.get_selected_name() == Some(bumped.clone()) {
+            true => format!("Selected {}", bumped),
+            false => format!("{} is hidden by the filter", bumped),
+        }
+    }
+
+    /// why the tag can't be written for the repository, when the list doesn't show its tags
+    /// or the tag is not among the loaded ones
+    pub fn check_tag(&self, repo: &str, tag: &str) -> Result<(), String> {
+        match &self.repo {
+            Some(listed) if listed != repo => {
+                return Err(format!(
+                    "The tags are the ones of {}, fetch the tags of {} first",
+                    listed, repo
+                ))
+            }
+            _ => (),
+        }
+        match self.get_tags().iter().any(|t| t.get_name() == tag) {
+            true => Ok(()),
+            false => Err(format!("{} is not a loaded tag of {}", tag, repo)),
+        }
+    }
+
+    /// the name of the repository, when the tags were listed for one
+    pub fn get_repo(&self) -> Option<&str> {
+        self.repo.as_deref()
+    }
+
+    /// the tags which were not compared with the last visit yet
+    pub fn unchecked_tags(&self) -> Vec<repository::Tag> {
+        self.lines
+            .iter()
+            .filter_map(|l| match l {
+                Line::Image(t) => Some(t),
+                _ => None,
+            })
+            .skip(self.checked)
+            .cloned()
+            .collect()
+    }
+
+    /// mark tags whose digest changed since the last visit, after checking all tags up to now
+    pub fn add_moved(&mut self, moved: Vec<String>) {
+        self.checked = self
+            .lines
+            .iter()
+            .filter(|l| matches!(l, Line::Image(_)))
+            .count();
+        self.moved.extend(moved);
+    }
+
+    /// shows the frame in the title, none when no tags are fetched
+    pub fn set_spinner(&mut self, spinner: Option<char>) {
+        self.spinner = spinner;
+    }
+
+    /// whether the tags of a repository are shown
+    pub fn is_loaded(&self) -> bool {
+        self.tags.is_some()
+    }
+
+    /// take over the search, marks and selection of the list this one replaces
+    pub fn keep_state_of(&mut self, old: &TagList) {
+        self.search = old.search.clone();
+        self.case_sensitive = old.case_sensitive;
+        self.marked = old.marked.clone();
+        self.repo = self.repo.take().or_else(|| old.repo.clone());
+        self.moved.extend(old.moved.iter().cloned());
+        self.grouped = old.grouped;
+        self.by_version = old.by_version;
+        self.expanded = old.expanded.clone();
+        self.update_visible();
+        if let Some(name) = old.get_selected_name() {
+            self.select_tag(&name);
+        }
+    }
+
+    /// change the filter and keep the selected line if possible
+    pub fn set_filter(&mut self, filter: &Filter) {
+        self.filter = filter.clone();
+        self.update_visible();
+    }
+
+    /// show tags with the same digest as one line or each on its own and describe it
+    pub fn toggle_grouping(&mut self) -> &'static str {
+        self.grouped = !self.grouped;
+        let digest = self.selected_digest();
+        self.update_visible();
+        self.select_digest(digest);
+        let digests = self.get_tags().iter().any(|t| t.get_digest().is_some());
+        match (self.grouped, digests) {
+            (false, _) => "Showing every tag on its own line",
+            (true, true) => "Grouping tags of the same image, → expands and ← collapses a group",
+            (true, false) => "The registry lists no digests, so tags can't be grouped",
+        }
+    }
+
+    /// sort the tags by version, newest first, or show them as the registry lists them and describe it,
+    /// the selected tag stays selected
+    pub fn toggle_sort_by_version(&mut self) -> &'static str {
+        self.by_version = !self.by_version;
+        self.update_visible();
+        match self.by_version {
+            true => "Sorting tags by version, newest first",
+            false => "Showing tags in the order of the registry",
+        }
+    }
+
+    /// show all tags of the selected group or only its first one
+    fn set_expanded(&mut self, expand: bool) {
+        let digest = match self.selected_digest() {
+            Some(digest) if self.grouped => digest,
+            _ => return,
+        };
+        match expand {
+            true => self.expanded.insert(digest.clone()),
+            false => self.expanded.remove(&digest),
+        };
+        self.update_visible();
+        self.select_digest(Some(digest));
+    }
+
+    fn selected_digest(&self) -> Option<String> {
+        match self.selected_line() {
+            Some(Line::Image(t)) => t.get_digest().map(String::from),
+            _ => None,
+        }
+    }
+
+    /// select the first visible tag with the digest, when the selected line was hidden
+    fn select_digest(&mut self, digest: Option<String>) {
+        if self.state.selected().is_some() || digest.is_none() {
+            return;
+        }
+        let position = self.visible.iter().position(|&i| match &self.lines[i] {
+            Line::Image(t) => t.get_digest() == digest.as_deref(),
+            _ => false,
+        });
+        self.state.select(position);
+    }
+
+    fn is_expanded(&self, tag: &repository::Tag) -> bool {
+        match tag.get_digest() {
+            Some(digest) => self.expanded.contains(digest),
+            None => false,
+        }
+    }
+
+    /// the names of the other loaded tags with the same digest
+    fn aliases(&self, tag: &repository::Tag) -> Vec<&str> {
+        let digest = match tag.get_digest() {
+            Some(digest) => digest,
+            None => return vec![],
+        };
+        self.lines
+            .iter()
+            .filter_map(|l| match l {
+                Line::Image(t)
+                    if t.get_digest() == Some(digest) && t.get_name() != tag.get_name() =>
+                {
+                    Some(t.get_name())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// recalculate which lines pass the filter
+    fn update_visible(&mut self) {
+        let selected = self.state.selected().map(|i| self.visible[i]);
+        let search = self.search.to_lowercase();
+        let contains_search = |name: &str| match self.case_sensitive {
+            true => name.contains(&self.search),
+            false => name.to_lowercase().contains(&search),
+        };
+        let visible = self
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(_, l)| match l {
+                Line::Image(t) => {
+                    self.filter.matches(t, self.repo.as_deref()) && contains_search(t.get_name())
+                }
+                _ => true,
+            })
+            .map(|(i, _)| i)
+            .collect();
+        let mut visible = match self.filter.newest_per_variant {
+            true => newest_per_variant(&self.lines, visible, &self.filter.variants),
+            false => visible,
+        };
+        if self.by_version {
+            // the status and next page lines stay at the end
+            visible.sort_by(|&a, &b| match (&self.lines[a], &self.lines[b]) {
+                (Line::Image(a), Line::Image(b)) => {
+                    version::newest_first(a.get_name(), b.get_name())
+                }
+                (Line::Image(_), _) => Ordering::Less,
+                (_, Line::Image(_)) => Ordering::Greater,
+                _ => Ordering::Equal,
+            });
         }
+        self.visible = match self.grouped {
+            true => first_of_groups(&self.lines, visible, &self.expanded),
+            false => visible,
+        };
+        self.state
+            .select(selected.and_then(|s| self.visible.iter().position(|&i| i == s)));
     }
 
-    pub fn render(&mut self, colored: bool) -> (List, &mut ListState) {
+    /// the currently selected line
+    fn selected_line(&self) -> Option<&Line> {
+        self.state.selected().map(|i| &self.lines[self.visible[i]])
+    }
+
+    pub fn render(&mut self, colored: bool, view: ListView) -> (List<'_>, &mut ListState) {
         let border_style = if colored {
             Style::default().fg(Color::Green)
         } else {
@@ -93,19 +564,97 @@ impl TagList {
         };
 
         let items: Vec<tui::widgets::ListItem> = self
-            .lines
+            .visible
             .iter()
-            .map(|l| {
-                tui::widgets::ListItem::new(format!("{}", l))
+            .enumerate()
+            .map(|(n, &i)| {
+                let mark = match &self.lines[i] {
+                    Line::Image(t) if self.marked.contains(t.get_name()) => "[x] ",
+                    Line::Image(_) if !self.marked.is_empty() => "[ ] ",
+                    _ => "",
+                };
+                let prefix = match view.accessible {
+                    true => format!("{}. {}", n + 1, mark),
+                    false => mark.to_string(),
+                };
+                let highlight = match view.accessible {
+                    true => Style::default().add_modifier(Modifier::BOLD),
+                    false => Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                };
+                let line = match &self.lines[i] {
+                    Line::Image(t) if view.platform_count => match platform_count(t) {
+                        Some(count) => format!("{}{}   [{} arch]", prefix, self.lines[i], count),
+                        None => format!("{}{}", prefix, self.lines[i]),
+                    },
+                    line => format!("{}{}", prefix, line),
+                };
+                let line = match &self.lines[i] {
+                    Line::Image(t) if self.moved.contains(t.get_name()) => {
+                        format!("{}   ⟳ moved", line)
+                    }
+                    _ => line,
+                };
+                let line = match &self.lines[i] {
+                    Line::Image(t) => match os_label(t) {
+                        Some(label) => format!("{}   [{}]", line, label),
+                        None => line,
+                    },
+                    _ => line,
+                };
+                let line = match &self.lines[i] {
+                    Line::Image(t)
+                        if (self.grouped || self.filter.newest_per_variant)
+                            && !self.is_expanded(t) =>
+                    {
+                        match self.aliases(t) {
+                            aliases if aliases.is_empty() => line,
+                            aliases => format!("{}   (also {})", line, aliases.join(", ")),
+                        }
+                    }
+                    _ => line,
+                };
+                let text = match &self.lines[i] {
+                    Line::Image(t) => {
+                        match find_match(t.get_name(), &self.search, self.case_sensitive) {
+                            // the name is at the start of the line, right after the prefix
+                            Some((start, end)) => Spans::from(vec![
+                                Span::raw(line[..prefix.len() + start].to_string()),
+                                Span::styled(
+                                    line[prefix.len() + start..prefix.len() + end].to_string(),
+                                    highlight,
+                                ),
+                                Span::raw(line[prefix.len() + end..].to_string()),
+                            ]),
+                            None => Spans::from(line),
+                        }
+                    }
+                    _ => Spans::from(line),
+                };
+                tui::widgets::ListItem::new(text)
                     .style(Style::default().fg(Color::White).bg(Color::Black))
             })
             .collect();
 
+        let title = match (self.search.is_empty(), self.case_sensitive) {
+            (true, false) => String::from("Tags"),
+            (true, true) => String::from("Tags (case sensitive)"),
+            (false, false) => format!("Tags (filter: {})", self.search),
+            (false, true) => format!("Tags (filter: {}, case sensitive)", self.search),
+        };
+        let title = match self.spinner {
+            // screen readers would read the changing frames again and again
+            Some(_) if view.accessible => format!("{}, fetching", title),
+            Some(frame) => format!("{} {}", title, frame),
+            None => title,
+        };
+
         // Create a List from all list items and highlight the currently selected one
         let items = List::new(items)
             .block(
                 Block::default()
-                    .title("Tags")
+                    .title(title)
                     .borders(Borders::ALL)
                     .border_style(border_style),
             )
@@ -116,15 +665,35 @@ impl TagList {
         (items, &mut self.state)
     }
 
-    pub fn create_detail_widget(&self) -> crate::widget::details::Details {
+    /// the selected line as plain text for screen readers
+    pub fn describe_selected(&self) -> Option<String> {
+        let i = self.state.selected()?;
+        let line = match &self.lines[self.visible[i]] {
+            Line::Image(t) => t.get_name().to_string(),
+            line => format!("{}", line),
+        };
+        Some(format!(
+            "Selected {} of {}: {}",
+            i + 1,
+            self.visible.len(),
+            line
+        ))
+    }
+
+    pub fn create_detail_widget(
+        &self,
+        view: crate::widget::details::View,
+    ) -> crate::widget::details::Details {
         use crate::widget::details::Details;
 
-        match self.state.selected() {
-            None => Details::new(),
-            Some(i) => match &self.lines[i] {
-                Line::Image(t) => Details::with_list(t.get_details()),
-                _ => Details::new(),
-            },
+        match self.selected_line() {
+            Some(Line::Image(t)) => {
+                let mut details = Details::with_list(t.get_details(), view);
+                details.set_dates(t.get_dates());
+                details.set_digest(t.pin_digest());
+                details
+            }
+            _ => Details::new(),
         }
     }
 
@@ -132,73 +701,206 @@ impl TagList {
         match key {
             Key::Down => self.next(),
             Key::Up => self.previous(),
+            Key::Home if !self.visible.is_empty() => self.state.select(Some(0)),
+            Key::End if !self.visible.is_empty() => self.state.select(Some(self.visible.len() - 1)),
             Key::Char('\n') => self.select(),
+            Key::Char(' ') => self.toggle_mark(),
+            Key::Right => self.set_expanded(true),
+            Key::Left => self.set_expanded(false),
+            Key::Char(c) => {
+                self.search.push(c);
+                self.update_visible();
+            }
+            Key::Backspace => {
+                self.search.pop();
+                self.update_visible();
+            }
+            Key::Esc => {
+                self.search.clear();
+                self.update_visible();
+            }
             _ => (),
         }
     }
 
     /// loads new tags when matching line is selected
     fn select(&mut self) {
-        if let Some(i) = self.state.selected() {
-            if let Line::NextPage(_) = &self.lines[i] {
-                self.load_next_page()
-            }
+        if let Some(Line::NextPage(_)) = self.selected_line() {
+            self.load_next_page()
         }
     }
 
     pub fn get_selected(&mut self) -> Result<String, Error> {
-        match self.state.selected() {
-            None => Err(Error::NoneSelected),
-            Some(i) => match &self.lines[i] {
-                Line::Status(_) => Err(Error::SelectedStatus),
-                Line::Image(i) => Ok(i.get_name().to_string()),
-                Line::NextPage(_) => {
-                    self.load_next_page();
-                    Err(Error::NextPageSelected)
+        match self.selected_line() {
+            None => self.get_unique_match(),
+            Some(Line::Status(_)) => Err(Error::SelectedStatus),
+            Some(Line::Image(i)) => Ok(i.get_name().to_string()),
+            Some(Line::NextPage(_)) => {
+                self.load_next_page();
+                Err(Error::NextPageSelected)
+            }
+        }
+    }
+
+    /// the name and the digest to pin to of the selected tag, when the registry lists one
+    pub fn get_selected_digest(&self) -> Option<(String, Option<String>)> {
+        match self.selected_line() {
+            Some(Line::Image(t)) => {
+                Some((t.get_name().to_string(), t.pin_digest().map(String::from)))
+            }
+            _ => None,
+        }
+    }
+
+    /// the name of the selected tag without loading further pages
+    pub fn get_selected_name(&self) -> Option<String> {
+        match self.selected_line() {
+            Some(Line::Image(t)) => Some(t.get_name().to_string()),
+            _ => None,
+        }
+    }
+
+    /// all loaded tags
+    pub fn get_tags(&self) -> Vec<repository::Tag> {
+        self.lines
+            .iter()
+            .filter_map(|l| match l {
+                Line::Image(t) => Some(t.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// select the tag with the given name, if it is visible
+    pub fn select_tag(&mut self, name: &str) {
+        let position = self.visible.iter().position(|&i| match &self.lines[i] {
+            Line::Image(t) => t.get_name() == name,
+            _ => false,
+        });
+        if position.is_some() {
+            self.state.select(position);
+        }
+    }
+
+    /// mark or unmark the selected tag
+    fn toggle_mark(&mut self) {
+        if let Some(Line::Image(t)) = self.selected_line() {
+            let name = t.get_name().to_string();
+            if !self.marked.remove(&name) {
+                self.marked.insert(name);
+            }
+        }
+    }
+
+    /// the names of all marked tags in the order of the list
+    pub fn get_marked(&self) -> Vec<String> {
+        self.lines
+            .iter()
+            .filter_map(|l| match l {
+                Line::Image(t) if self.marked.contains(t.get_name()) => {
+                    Some(t.get_name().to_string())
                 }
-            },
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// the names of the listed tags in their order, without the ones hidden by filters
+    pub fn get_visible_names(&self) -> Vec<String> {
+        self.visible
+            .iter()
+            .filter_map(|&i| match &self.lines[i] {
+                Line::Image(t) => Some(t.get_name().to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// the only tag matching the typed filter
+    fn get_unique_match(&self) -> Result<String, Error> {
+        if self.search.is_empty() {
+            return Err(Error::NoneSelected);
+        }
+
+        let matches: Vec<&repository::Tag> = self
+            .visible
+            .iter()
+            .filter_map(|&i| match &self.lines[i] {
+                Line::Image(t) => Some(t),
+                _ => None,
+            })
+            .collect();
+        match matches.len() {
+            0 => Err(Error::NoneSelected),
+            1 => Ok(matches[0].get_name().to_string()),
+            n => Err(Error::AmbiguousMatch(n)),
         }
     }
 
-    /// load new tags from the next page
+    /// load new tags from the next page in the background, so the ui keeps drawing
+    /// while the registry is asked again after failures
     fn load_next_page(&mut self) {
-        match &self.tags {
-            Some(tags) => match tags.next_page() {
-                None => (),
-                Some(new_tags) => {
-                    //load new tags object
-                    self.tags = Some(new_tags);
-
-                    //remove "load next page"
-                    let next_page = self.lines.pop();
-
-                    //add tags
-                    match &self.tags {
-                        None => (),
-                        Some(tags) => {
-                            for image in tags.get_tags().iter() {
-                                self.lines.push(Line::Image(image.clone()));
-                            }
-                        }
-                    }
+        let tags = match &self.tags {
+            Some(tags) if tags.has_next_page() && self.next_page.is_none() => tags.clone(),
+            _ => return,
+        };
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            if let Some(page) = tags.next_page() {
+                // the receiver is gone, when another list replaced this one
+                let _ = tx.send(page);
+            }
+        });
+        self.next_page = Some(rx);
+        if let Some(Line::NextPage(text)) = self.lines.last_mut() {
+            *text = String::from("loading more tags…");
+        }
+    }
 
-                    //readd next page
-                    match self.tags.as_ref().unwrap().next_page() {
-                        None => (),
-                        Some(_) => self.lines.push(next_page.unwrap()),
-                    }
+    /// adds the next page once it arrived, a failed page can be selected again,
+    /// returns whether the list changed
+    pub fn poll_next_page(&mut self) -> bool {
+        let page = match self.next_page.as_ref().map(|rx| rx.try_recv()) {
+            None | Some(Err(mpsc::TryRecvError::Empty)) => return false,
+            Some(Ok(page)) => page.map_err(|e| format!("{}", e)),
+            Some(Err(mpsc::TryRecvError::Disconnected)) => Err(String::from("aborted")),
+        };
+        self.next_page = None;
+        match page {
+            Ok(page) => self.append_page(page),
+            Err(e) => {
+                if let Some(Line::NextPage(text)) = self.lines.last_mut() {
+                    *text = format!("could not load more tags ({}), select to retry", e);
                 }
-            },
-            None => (),
+            }
         }
+        true
+    }
+
+    /// adds the tags of the page in place of "load more tags", the selection stays where it is
+    fn append_page(&mut self, page: repository::Repo) {
+        //remove "load more tags"
+        if let Some(Line::NextPage(_)) = self.lines.last() {
+            self.lines.pop();
+        }
+
+        for image in page.get_tags().iter() {
+            self.lines.push(Line::Image(image.clone()));
+        }
+        if page.has_next_page() {
+            self.lines
+                .push(Line::NextPage(String::from("load more tags")));
+        }
+        self.tags = Some(page);
+        self.update_visible();
     }
 
     /// select next tag
     fn next(&mut self) {
         match self.state.selected() {
-            None if !self.lines.is_empty() => self.state.select(Some(0)),
+            None if !self.visible.is_empty() => self.state.select(Some(0)),
             None => (),
-            Some(i) if i == self.lines.len() - 1 => self.state.select(Some(0)),
+            Some(i) if i + 1 >= self.visible.len() => self.state.select(Some(0)),
             Some(i) => self.state.select(Some(i + 1)),
         }
     }
@@ -206,10 +908,553 @@ impl TagList {
     /// select previous tag
     fn previous(&mut self) {
         match self.state.selected() {
-            None if !self.lines.is_empty() => self.state.select(Some(self.lines.len())),
+            None if !self.visible.is_empty() => self.state.select(Some(self.visible.len() - 1)),
             None => (),
-            Some(i) if i == 0 => self.state.select(Some(self.lines.len() - 1)),
+            Some(0) => self.state.select(Some(self.visible.len() - 1)),
             Some(i) => self.state.select(Some(i - 1)),
         }
     }
 }
+
+/// the byte range of the first occurrence of the search in the name
+fn find_match(name: &str, search: &str, case_sensitive: bool) -> Option<(usize, usize)> {
+    if case_sensitive {
+        let start = name.find(search).filter(|_| !search.is_empty())?;
+        return Some((start, start + search.len()));
+    }
+    let lowercase = name.to_lowercase();
+    // offsets of the lowercase version are only valid if the length did not change
+    if search.is_empty() || lowercase.len() != name.len() {
+        return None;
+    }
+    let search = search.to_lowercase();
+    let start = lowercase.find(&search)?;
+    Some((start, start + search.len()))
+}
+
+/// keeps only the first of the lines whose tags share a digest, unless their group is expanded
+fn first_of_groups(lines: &[Line], visible: Vec<usize>, expanded: &HashSet<String>) -> Vec<usize> {
+    let mut seen = HashSet::new();
+    visible
+        .into_iter()
+        .filter(|&i| match &lines[i] {
+            Line::Image(t) => match t.get_digest() {
+                Some(digest) if !expanded.contains(digest) => seen.insert(digest),
+                _ => true,
+            },
+            _ => true,
+        })
+        .collect()
+}
+
+/// keeps only the most recently updated tag of each variant family, the first one on a tie
+fn newest_per_variant(
+    lines: &[Line],
+    visible: Vec<usize>,
+    patterns: &[regex::Regex],
+) -> Vec<usize> {
+    let mut newest: HashMap<&str, usize> = HashMap::new();
+    for &i in &visible {
+        if let Line::Image(t) = &lines[i] {
+            let family = version::variant(t.get_name(), patterns);
+            match newest.get(family) {
+                Some(&j) if !is_newer(t, &lines[j]) => (),
+                _ => {
+                    newest.insert(family, i);
+                }
+            }
+        }
+    }
+    visible
+        .into_iter()
+        .filter(|i| match &lines[*i] {
+            Line::Image(_) => newest.values().any(|j| j == i),
+            _ => true,
+        })
+        .collect()
+}
+
+fn is_newer(tag: &repository::Tag, other: &Line) -> bool {
+    match other {
+        Line::Image(other) => tag.last_updated() > other.last_updated(),
+        _ => false,
+    }
+}
+
+/// the operating systems a tag has images for, sorted
+fn operating_systems(tag: &repository::Tag) -> Vec<&str> {
+    let mut systems: Vec<&str> = tag
+        .get_details()
+        .iter()
+        .filter_map(|d| d.os.as_deref())
+        .filter(|os| !os.is_empty())
+        .collect();
+    systems.sort_unstable();
+    systems.dedup();
+    systems
+}
+
+/// names the operating systems of a tag, unless it only has linux images like most
+fn os_label(tag: &repository::Tag) -> Option<String> {
+    match operating_systems(tag).as_slice() {
+        [] | ["linux"] => None,
+        systems => Some(systems.join("+")),
+    }
+}
+
+/// the number of different platforms of a tag, when the registry tells them
+fn platform_count(tag: &repository::Tag) -> Option<usize> {
+    let platforms: HashSet<_> = tag
+        .get_details()
+        .iter()
+        .filter(|d| d.arch.is_some())
+        .map(|d| (&d.os, &d.arch, &d.variant))
+        .collect();
+    match platforms.len() {
+        0 => None,
+        count => Some(count),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_find_match() {
+        type Case<'a> = (&'a str, &'a str, bool, Option<(usize, usize)>);
+        let input: Vec<Case> = vec![
+            ("1.25-alpine", "alp", false, Some((5, 8))),
+            ("V2-Slim", "v2-s", false, Some((0, 4))),
+            ("latest", "", false, None),
+            ("latest", "alpine", false, None),
+            ("V2-Slim", "v2-s", true, None),
+            ("v2-V2", "V2", true, Some((3, 5))),
+            ("latest", "", true, None),
+        ];
+
+        for i in input {
+            assert_eq!(super::find_match(i.0, i.1, i.2), i.3, "{} {}", i.0, i.1);
+        }
+    }
+
+    #[test]
+    fn test_first_of_groups() {
+        use std::collections::HashSet;
+
+        use super::Line;
+
+        let tag = |name: &str, digest: Option<&str>| {
+            let digest = match digest {
+                Some(d) => format!("\"{}\"", d),
+                None => String::from("null"),
+            };
+            let json = format!(
+                r#"{{"name": "{}", "last_updated": null, "details": [], "digest": {}}}"#,
+                name, digest
+            );
+            Line::Image(serde_json::from_str(&json).unwrap())
+        };
+        let lines = vec![
+            tag("latest", Some("sha256:a")),
+            tag("1.25", Some("sha256:a")),
+            tag("1.24", Some("sha256:b")),
+            tag("old", None),
+            tag("older", None),
+            tag("1", Some("sha256:a")),
+            Line::NextPage(String::from("load more tags")),
+        ];
+        let all: Vec<usize> = (0..lines.len()).collect();
+
+        let input: Vec<(Vec<usize>, Vec<&str>, Vec<usize>)> = vec![
+            (all.clone(), vec![], vec![0, 2, 3, 4, 6]),
+            // the first tag of a group may be hidden by the filter
+            (vec![1, 2, 5], vec![], vec![1, 2]),
+            (all.clone(), vec!["sha256:a"], all.clone()),
+        ];
+
+        for i in input {
+            let expanded: HashSet<String> = i.1.iter().map(|d| d.to_string()).collect();
+            assert_eq!(super::first_of_groups(&lines, i.0, &expanded), i.2);
+        }
+    }
+
+    #[test]
+    fn test_newest_per_variant() {
+        use super::Line;
+
+        let tag = |name: &str, updated: &str| {
+            let json = format!(
+                r#"{{"name": "{}", "last_updated": "{}", "details": []}}"#,
+                name, updated
+            );
+            Line::Image(serde_json::from_str(&json).unwrap())
+        };
+        let lines = vec![
+            tag("1.25.3", "2024-01-01T00:00:00Z"),
+            tag("1.25.4", "2024-02-01T00:00:00Z"),
+            tag("1.25.4-alpine", "2024-02-01T00:00:00Z"),
+            tag("1.25.3-alpine", "2024-01-01T00:00:00Z"),
+            tag("latest", "2024-02-01T00:00:00Z"),
+            Line::NextPage(String::from("load more tags")),
+        ];
+        let all: Vec<usize> = (0..lines.len()).collect();
+        let alpine = vec![regex::Regex::new("alpine").unwrap()];
+
+        let input: Vec<(Vec<usize>, &[regex::Regex], Vec<usize>)> = vec![
+            (all.clone(), &[], vec![1, 2, 5]),
+            (vec![0, 3], &[], vec![0, 3]),
+            (all.clone(), &alpine, vec![1, 2, 5]),
+        ];
+
+        for i in input {
+            assert_eq!(super::newest_per_variant(&lines, i.0, i.1), i.2);
+        }
+    }
+
+    #[test]
+    fn test_os_label() {
+        let tag = |systems: &[&str]| -> crate::repository::Tag {
+            let details: Vec<String> = systems
+                .iter()
+                .map(|os| {
+                    format!(
+                        r#"{{"arch": "amd64", "variant": null, "os": "{}", "size": 1}}"#,
+                        os
+                    )
+                })
+                .collect();
+            serde_json::from_str(&format!(
+                r#"{{"name": "1.25", "last_updated": null, "details": [{}]}}"#,
+                details.join(",")
+            ))
+            .unwrap()
+        };
+        let input: Vec<(&[&str], Option<&str>)> = vec![
+            (&[], None),
+            (&["linux", "linux"], None),
+            (&["windows"], Some("windows")),
+            (&["windows", "linux", "windows"], Some("linux+windows")),
+        ];
+
+        for i in input {
+            assert_eq!(super::os_label(&tag(i.0)).as_deref(), i.1);
+        }
+
+        let mut filter = super::Filter::default();
+        assert_eq!(filter.cycle_os(), "Showing only tags with a linux image");
+        assert!(!filter.matches(&tag(&["windows"]), None));
+        assert!(filter.matches(&tag(&[]), None));
+        filter.cycle_os();
+        assert!(filter.matches(&tag(&["windows", "linux"]), None));
+        assert_eq!(filter.cycle_os(), "Showing tags of any os");
+    }
+
+    #[test]
+    fn test_newer_than() {
+        let tag = |updated: &str| -> crate::repository::Tag {
+            serde_json::from_str(&format!(
+                r#"{{"name": "1.25", "last_updated": {}, "details": []}}"#,
+                updated
+            ))
+            .unwrap()
+        };
+        let current = tag(r#""2024-03-01T12:00:00Z""#).last_updated().unwrap();
+        let mut filter = super::Filter::default();
+        assert_eq!(
+            filter.set_newer_than(Some(super::NewerThan {
+                repo: "library/nginx".into(),
+                tag: "1.25".into(),
+                updated: current,
+            })),
+            "Showing tags updated after 1.25"
+        );
+
+        let input: Vec<(&str, bool)> = vec![
+            (r#""2024-04-01T12:00:00Z""#, true),
+            (r#""2024-03-01T12:00:00Z""#, false),
+            (r#""2024-01-01T12:00:00Z""#, false),
+            ("null", true),
+        ];
+        for i in input {
+            let nginx = Some("library/nginx");
+            assert_eq!(filter.matches(&tag(i.0), nginx), i.1, "{}", i.0);
+        }
+        // the cutoff doesn't apply to the tags of other repos
+        let old = tag(r#""2024-01-01T12:00:00Z""#);
+        assert!(filter.matches(&old, Some("library/httpd")));
+        assert!(filter.matches(&old, None));
+
+        filter.set_newer_than(None);
+        assert!(filter.matches(&old, Some("library/nginx")));
+    }
+
+    #[test]
+    fn test_platform_count() {
+        let tag: crate::repository::Tag = serde_json::from_str(
+            r#"{"name": "1.25", "last_updated": null, "details": [
+                {"arch": "amd64", "variant": "", "os": "linux", "size": 1},
+                {"arch": "arm", "variant": "v7", "os": "linux", "size": 1},
+                {"arch": "arm", "variant": "v7", "os": "linux", "size": 2}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(super::platform_count(&tag), Some(2));
+
+        let tag: crate::repository::Tag = serde_json::from_str(
+            r#"{"name": "1.25", "last_updated": null, "details": [
+                {"arch": null, "variant": null, "os": null, "size": 1}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(super::platform_count(&tag), None);
+    }
+
+    #[test]
+    fn test_get_visible_names() {
+        let tags: Vec<crate::repository::Tag> = serde_json::from_str(
+            r#"[
+                {"name": "1.25-alpine", "last_updated": null, "details": []},
+                {"name": "1.25", "last_updated": null, "details": []},
+                {"name": "1.24-alpine", "last_updated": null, "details": []}
+            ]"#,
+        )
+        .unwrap();
+        let repo = crate::repository::Repo::from_tags(tags);
+        let mut list = super::TagList::with_tags(repo, &super::Filter::new(None));
+        assert_eq!(
+            list.get_visible_names(),
+            vec!["1.25-alpine", "1.25", "1.24-alpine"]
+        );
+
+        for c in "alp".chars() {
+            list.handle_input(termion::event::Key::Char(c));
+        }
+        assert_eq!(list.get_visible_names(), vec!["1.25-alpine", "1.24-alpine"]);
+    }
+
+    #[test]
+    fn test_append_page() {
+        use super::{Filter, TagList};
+        use crate::repository::{Repo, Settings, Tag};
+
+        let tags = |names: &[&str]| -> Vec<Tag> {
+            names
+                .iter()
+                .map(|n| {
+                    serde_json::from_str(&format!(
+                        r#"{{"name": "{}", "details": [], "last_updated": null}}"#,
+                        n
+                    ))
+                    .unwrap()
+                })
+                .collect()
+        };
+        let first = Repo::with_next_url(
+            tags(&["1.25", "1.24"]),
+            "https://hub.docker.com/v2/repositories/library/nginx/tags?page=2",
+            &Settings::default(),
+        );
+        let mut list = TagList::with_tags(first, &Filter::default());
+        assert_eq!(list.lines.len(), 3);
+        assert_eq!(list.lines[2].to_string(), "load more tags");
+
+        list.state.select(Some(1));
+        list.append_page(Repo::with_next_url(
+            tags(&["1.23", "1.22"]),
+            "https://hub.docker.com/v2/repositories/library/nginx/tags?page=3",
+            &Settings::default(),
+        ));
+        assert_eq!(
+            list.get_visible_names(),
+            vec!["1.25", "1.24", "1.23", "1.22"]
+        );
+        assert_eq!(list.get_selected_name(), Some("1.24".to_string()));
+        assert_eq!(list.lines.len(), 5);
+
+        // the first new tag takes the place of the selected "load more tags"
+        list.state.select(Some(4));
+        list.append_page(Repo::from_tags(tags(&["1.21"])));
+        assert_eq!(list.get_selected_name(), Some("1.21".to_string()));
+        assert_eq!(list.lines.len(), 5);
+    }
+
+    #[test]
+    fn test_load_next_page() {
+        use super::{Filter, TagList};
+        use std::io::{Read, Write};
+        use termion::event::Key;
+
+        // a registry with a second page of a single tag
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/tags?page=2", listener.local_addr().unwrap());
+        let registry = std::thread::spawn(move || {
+            let mut stream = listener.incoming().next().unwrap().unwrap();
+            let _ = stream.read(&mut [0; 1024]);
+            let body = r#"{"next": null, "results": [{"name": "1.23"}]}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        let first: Vec<crate::repository::Tag> =
+            serde_json::from_str(r#"[{"name": "1.25", "details": [], "last_updated": null}]"#)
+                .unwrap();
+        let mut list = TagList::with_tags(
+            crate::repository::Repo::with_next_url(first, &url, &Default::default()),
+            &Filter::default(),
+        );
+
+        list.state.select(Some(1));
+        list.handle_input(Key::Char('\n'));
+        assert_eq!(list.lines[1].to_string(), "loading more tags…");
+        for _ in 0..100 {
+            if list.poll_next_page() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(list.get_visible_names(), vec!["1.25", "1.23"]);
+        registry.join().unwrap();
+    }
+
+    #[test]
+    fn test_sort_by_version() {
+        let tags: Vec<crate::repository::Tag> = serde_json::from_str(
+            r#"[
+                {"name": "latest", "last_updated": null, "details": []},
+                {"name": "1.25.3", "last_updated": null, "details": []},
+                {"name": "20240301", "last_updated": null, "details": []},
+                {"name": "v1.27.0-alpine", "last_updated": null, "details": []},
+                {"name": "1.9.12", "last_updated": null, "details": []},
+                {"name": "mainline", "last_updated": null, "details": []},
+                {"name": "1.25.10", "last_updated": null, "details": []}
+            ]"#,
+        )
+        .unwrap();
+        let repo = crate::repository::Repo::with_next_url(
+            tags,
+            "https://hub.docker.com/v2/repositories/library/nginx/tags?page=2",
+            &Default::default(),
+        );
+        let mut list = super::TagList::with_tags(repo, &super::Filter::new(None));
+        list.state.select(Some(1));
+
+        list.toggle_sort_by_version();
+        assert_eq!(
+            list.get_visible_names(),
+            vec![
+                "v1.27.0-alpine",
+                "1.25.10",
+                "1.25.3",
+                "1.9.12",
+                "20240301",
+                "latest",
+                "mainline"
+            ]
+        );
+        assert_eq!(list.get_selected_name(), Some("1.25.3".to_string()));
+        assert_eq!(
+            list.lines[*list.visible.last().unwrap()].to_string(),
+            "load more tags"
+        );
+
+        list.toggle_sort_by_version();
+        assert_eq!(list.get_visible_names()[..2], ["latest", "1.25.3"]);
+        assert_eq!(list.get_selected_name(), Some("1.25.3".to_string()));
+    }
+
+    #[test]
+    fn test_filter_as_typed() {
+        use termion::event::Key;
+
+        let tags: Vec<crate::repository::Tag> = serde_json::from_str(
+            r#"[
+                {"name": "1.25-Alpine", "last_updated": null, "details": []},
+                {"name": "1.25", "last_updated": null, "details": []},
+                {"name": "1.24-alpine", "last_updated": null, "details": []}
+            ]"#,
+        )
+        .unwrap();
+        let repo = crate::repository::Repo::from_tags(tags);
+        let mut list = super::TagList::with_tags(repo, &super::Filter::new(None));
+
+        for key in [
+            Key::Char('a'),
+            Key::Char('l'),
+            Key::Char('p'),
+            Key::Char('x'),
+        ] {
+            list.handle_input(key);
+        }
+        assert!(list.get_visible_names().is_empty());
+        list.handle_input(Key::Backspace);
+        assert_eq!(list.get_visible_names(), vec!["1.25-Alpine", "1.24-alpine"]);
+
+        list.handle_input(Key::Down);
+        list.handle_input(Key::Down);
+        assert_eq!(list.get_selected().ok(), Some("1.24-alpine".to_string()));
+
+        list.handle_input(Key::Esc);
+        assert_eq!(list.get_visible_names().len(), 3);
+        assert_eq!(list.get_selected().ok(), Some("1.24-alpine".to_string()));
+    }
+
+    #[test]
+    fn test_platform_filter() {
+        let tag = |platforms: &[(&str, &str)]| -> crate::repository::Tag {
+            let details: Vec<String> = platforms
+                .iter()
+                .map(|(arch, variant)| {
+                    format!(
+                        r#"{{"arch": "{}", "variant": "{}", "os": "linux", "size": 1}}"#,
+                        arch, variant
+                    )
+                })
+                .collect();
+            serde_json::from_str(&format!(
+                r#"{{"name": "1.25", "last_updated": null, "details": [{}]}}"#,
+                details.join(",")
+            ))
+            .unwrap()
+        };
+        let multi_arch = tag(&[("amd64", ""), ("arm64", "v8"), ("arm", "v7")]);
+        let single_arch = tag(&[("amd64", "")]);
+        let input: Vec<(&str, &crate::repository::Tag, bool)> = vec![
+            ("linux/arm64", &multi_arch, true),
+            ("linux/arm64", &single_arch, false),
+            ("linux/arm64/v8", &multi_arch, true),
+            ("linux/arm/v6", &multi_arch, false),
+            ("windows/amd64", &single_arch, false),
+            ("linux/amd64", &single_arch, true),
+        ];
+
+        for i in input {
+            let filter = super::Filter::default().with_platform(Some(i.0.parse().unwrap()));
+            assert_eq!(filter.matches(i.1, None), i.2, "{}", i.0);
+        }
+        assert!(super::Filter::default()
+            .with_platform(Some("linux/arm64".parse().unwrap()))
+            .matches(&tag(&[]), None));
+        assert!(super::Filter::default().matches(&single_arch, None));
+    }
+
+    #[test]
+    fn test_check_tag() {
+        let tags: Vec<crate::repository::Tag> =
+            serde_json::from_str(r#"[{"name": "1.25", "last_updated": null, "details": []}]"#)
+                .unwrap();
+        let list = super::TagList::with_fetched(
+            "library/nginx".to_string(),
+            Ok(crate::repository::Repo::from_tags(tags)),
+            &super::Filter::default(),
+        );
+
+        assert_eq!(list.check_tag("library/nginx", "1.25"), Ok(()));
+        assert!(list.check_tag("library/nginx", "1.26").is_err());
+        assert!(list.check_tag("library/httpd", "1.25").is_err());
+        assert!(super::TagList::with_status("Tags are empty")
+            .check_tag("library/nginx", "1.25")
+            .is_err());
+    }
+}